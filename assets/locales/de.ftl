@@ -0,0 +1,60 @@
+level = Level { $level }
+level-stats = { $time }  { $moves } Züge
+levels-header = Level  { $stars } Sterne
+best = { $time }  { $moves } Züge
+
+paused = Pause
+levels = Level
+settings = Einstellungen
+exit = Beenden
+
+graphics = Grafik
+screen-mode = Anzeige: { $mode }
+screen-mode-windowed = Fenster
+screen-mode-borderless = Randlos
+screen-mode-exclusive = Vollbild
+monitor = Monitor: { $monitor }
+resolution = Auflösung: { $resolution }
+resolution-native = Nativ
+vsync = VSync
+msaa = Kantenglättung
+bloom = Leuchten: { $bloom }
+bloom-off = Aus
+bloom-low = Schwach
+bloom-natural = Normal
+bloom-high = Stark
+
+controls = Steuerung
+action-pause = Pause
+action-undo = Rückgängig
+action-hint = Tipp
+action-restart = Neustart
+action-page-prev = Vorherige Seite
+action-page-next = Nächste Seite
+action-export = Rätsel exportieren
+reset-controls = Zurücksetzen
+
+theme = Farben: { $theme }
+theme-dark = Dunkel
+theme-light = Hell
+theme-high-contrast = Hoher Kontrast
+theme-retro = Retro
+language = Sprache: { $language }
+shape-cues = Formhinweise
+reduced-motion = Weniger Bewegung
+effects = Effekte
+mute = Stumm
+volume-master = Gesamt
+volume-effects = Effekte
+volume-music = Musik
+
+a11y-level = Level { $level }, { $solved } von { $total } Knoten gelöst
+a11y-vertex = Knoten { $number }, { $placed } von { $required } Kanten
+a11y-solved = gelöst
+a11y-over = zu viele Kanten
+a11y-connected = verbunden mit { $vertices }
+a11y-reach = verbindbar mit { $vertices }
+a11y-edge-placed = { $from } und { $to } verbunden
+a11y-edge-removed = Verbindung von { $from } und { $to } entfernt
+a11y-vertex-solved = Knoten { $number } gelöst
+a11y-level-solved = Level gelöst
@@ -0,0 +1,62 @@
+# English strings. Every other language falls back to these for missing keys.
+
+level = Level { $level }
+level-stats = { $time }  { $moves } moves
+levels-header = Levels  { $stars } stars
+best = { $time }  { $moves } moves
+
+paused = Paused
+levels = Levels
+settings = Settings
+exit = Exit
+
+graphics = Graphics
+screen-mode = Screen: { $mode }
+screen-mode-windowed = Windowed
+screen-mode-borderless = Borderless
+screen-mode-exclusive = Fullscreen
+monitor = Monitor: { $monitor }
+resolution = Resolution: { $resolution }
+resolution-native = Native
+vsync = VSync
+msaa = Anti-aliasing
+bloom = Glow: { $bloom }
+bloom-off = Off
+bloom-low = Low
+bloom-natural = Normal
+bloom-high = High
+
+controls = Controls
+action-pause = Pause
+action-undo = Undo
+action-hint = Hint
+action-restart = Restart
+action-page-prev = Previous page
+action-page-next = Next page
+action-export = Export puzzle
+reset-controls = Reset
+
+theme = Theme: { $theme }
+theme-dark = Dark
+theme-light = Light
+theme-high-contrast = High contrast
+theme-retro = Retro
+language = Language: { $language }
+shape-cues = Shape cues
+reduced-motion = Reduced motion
+effects = Effects
+mute = Mute
+volume-master = Master
+volume-effects = Effects
+volume-music = Music
+
+a11y-level = Level { $level }, { $solved } of { $total } vertices solved
+a11y-vertex = Vertex { $number }, { $placed } of { $required } edges
+a11y-solved = solved
+a11y-over = too many edges
+a11y-connected = connected to { $vertices }
+a11y-reach = can connect to { $vertices }
+a11y-edge-placed = Connected { $from } and { $to }
+a11y-edge-removed = Disconnected { $from } and { $to }
+a11y-vertex-solved = Vertex { $number } solved
+a11y-level-solved = Level solved
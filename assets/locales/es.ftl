@@ -0,0 +1,60 @@
+level = Nivel { $level }
+level-stats = { $time }  { $moves } movimientos
+levels-header = Niveles  { $stars } estrellas
+best = { $time }  { $moves } movimientos
+
+paused = Pausa
+levels = Niveles
+settings = Ajustes
+exit = Salir
+
+graphics = Gráficos
+screen-mode = Pantalla: { $mode }
+screen-mode-windowed = Ventana
+screen-mode-borderless = Sin bordes
+screen-mode-exclusive = Completa
+monitor = Monitor: { $monitor }
+resolution = Resolución: { $resolution }
+resolution-native = Nativa
+vsync = VSync
+msaa = Antialiasing
+bloom = Brillo: { $bloom }
+bloom-off = No
+bloom-low = Bajo
+bloom-natural = Normal
+bloom-high = Alto
+
+controls = Controles
+action-pause = Pausa
+action-undo = Deshacer
+action-hint = Pista
+action-restart = Reiniciar
+action-page-prev = Página anterior
+action-page-next = Página siguiente
+action-export = Exportar puzle
+reset-controls = Restablecer
+
+theme = Tema: { $theme }
+theme-dark = Oscuro
+theme-light = Claro
+theme-high-contrast = Alto contraste
+theme-retro = Retro
+language = Idioma: { $language }
+shape-cues = Formas
+reduced-motion = Menos movimiento
+effects = Efectos
+mute = Silencio
+volume-master = General
+volume-effects = Efectos
+volume-music = Música
+
+a11y-level = Nivel { $level }, { $solved } de { $total } vértices resueltos
+a11y-vertex = Vértice { $number }, { $placed } de { $required } aristas
+a11y-solved = resuelto
+a11y-over = demasiadas aristas
+a11y-connected = conectado a { $vertices }
+a11y-reach = se puede conectar a { $vertices }
+a11y-edge-placed = { $from } y { $to } conectados
+a11y-edge-removed = { $from } y { $to } desconectados
+a11y-vertex-solved = Vértice { $number } resuelto
+a11y-level-solved = Nivel resuelto
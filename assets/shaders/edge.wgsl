@@ -0,0 +1,48 @@
+#import bevy_sprite::mesh2d_vertex_output::VertexOutput
+#import bevy_sprite::mesh2d_view_bindings::globals
+
+// Length of a dash and the gap after it, in world units.
+const DASH_PERIOD: f32 = 30.0;
+// Spacing and speed of the bright bands that flow along solved edges, in world units.
+const FLOW_PERIOD: f32 = 120.0;
+const FLOW_SPEED: f32 = 300.0;
+
+struct EdgeMaterial {
+    bits: u32,
+    color: vec4<f32>,
+    blocked: vec4<f32>,
+}
+
+@group(2) @binding(0) var<uniform> material: EdgeMaterial;
+
+@fragment
+fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32> {
+    let bits = material.bits;
+    let preview = bits & 1;
+    let blocked = bits & 2;
+    let flow = bits & 4;
+    // The transform stretches the unit quad to the length of the edge, which is how many world
+    // units each step of `uv.x` covers.
+    let world_per_pixel = length(dpdx(mesh.world_position.xy));
+    let uv_per_pixel = length(vec2(dpdx(mesh.uv.x), dpdy(mesh.uv.x)));
+    let along = mesh.uv.x * world_per_pixel / uv_per_pixel;
+    // 0 on the center line of the edge and 1 on its sides.
+    let across = abs(mesh.uv.y - 0.5) * 2.0;
+
+    var color = material.color.rgb;
+    if blocked != 0 {
+        color = material.blocked.rgb;
+    }
+    if (preview != 0 || blocked != 0) && fract(along / DASH_PERIOD) > 0.6 {
+        discard;
+    }
+
+    // Brighter towards the center line, so bloom makes the edge glow.
+    var brightness = 1.0 + 0.8 * (1.0 - across) * (1.0 - across);
+    if flow != 0 {
+        let band = 0.5 + 0.5 * sin((along - globals.time * FLOW_SPEED) / FLOW_PERIOD * 6.28318531);
+        brightness += 2.0 * pow(band, 8.0);
+    }
+    let alpha = 1.0 - smoothstep(0.6, 1.0, across);
+    return vec4<f32>(color * brightness, alpha);
+}
@@ -1,12 +1,68 @@
 #import bevy_sprite::mesh2d_vertex_output::VertexOutput
+#import bevy_sprite::mesh2d_view_bindings::globals
 
-@group(2) @binding(0) var<uniform> bits: u32;
+const TAU: f32 = 6.28318531;
+
+struct VertexMaterial {
+    bits: u32,
+    placed: u32,
+    required: u32,
+    background: vec4<f32>,
+    foreground: vec4<f32>,
+    selected: vec4<f32>,
+    over: vec4<f32>,
+}
+
+@group(2) @binding(0) var<uniform> material: VertexMaterial;
+
+// Clockwise angle of `pos` from the top of the vertex, in [0, TAU).
+fn ring_angle(pos: vec2<f32>) -> f32 {
+    // `pos.y` points down in uv space.
+    let angle = atan2(pos.x, -pos.y);
+    if angle < 0.0 {
+        return angle + TAU;
+    }
+    return angle;
+}
+
+// Whether the segment of the progress ring at `pos` is filled.
+// Segments start at the top and go clockwise, one per required edge.
+fn ring_filled(pos: vec2<f32>) -> bool {
+    let angle = ring_angle(pos);
+    let segment_angle = TAU / f32(material.required);
+    let segment = u32(angle / segment_angle);
+    return segment < material.placed;
+}
+
+// Whether `pos` lies in the gap between two segments of the progress ring.
+fn ring_gap(pos: vec2<f32>) -> bool {
+    if material.required < 2 {
+        return false;
+    }
+    let angle = ring_angle(pos);
+    let segment_angle = TAU / f32(material.required);
+    let offset = angle - round(angle / segment_angle) * segment_angle;
+    return abs(offset) * length(pos) < 0.015;
+}
+
+// Whether `pos` lies on one of the diagonal stripes going in direction `dir`.
+fn stripe(pos: vec2<f32>, dir: vec2<f32>) -> bool {
+    return fract(dot(pos, dir) * 8.0) < 0.5;
+}
+
+// Whether `pos` lies on a dash of the outer rim, used to outline selected vertices.
+fn dashed_rim(pos: vec2<f32>) -> bool {
+    return length(pos) > 0.44 && fract(ring_angle(pos) / TAU * 12.0) < 0.5;
+}
 
 @fragment
 fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32> {
+    let bits = material.bits;
+    let pos = mesh.uv - vec2(0.5);
     let solved = bits & 2;
-    if solved == 0 && length(mesh.uv - vec2(0.5)) < 0.4 {
-        return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    let cues = bits & 8;
+    if solved == 0 && length(pos) < 0.4 {
+        return material.background;
     }
 
     var solved_multiplier = 1.0;
@@ -14,9 +70,37 @@ fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32> {
         solved_multiplier = 3.0;
     }
 
+    var color = vec4<f32>(material.foreground.rgb * solved_multiplier, 1.0);
+    let over = bits & 4;
     let selected = bits & 1;
     if selected != 0 {
-        return vec4<f32>(1.0, 0.5, 0.2, 1.0) * solved_multiplier;
+        color = vec4<f32>(material.selected.rgb * solved_multiplier, 1.0);
+    } else if over != 0 {
+        let pulse = 1.5 + 0.5 * sin(globals.time * 6.0);
+        color = vec4<f32>(material.over.rgb * pulse, 1.0);
+    }
+
+    if cues != 0 {
+        // Selected vertices get a dashed outline, over-saturated ones a cross-hatched ring and
+        // solved ones a striped fill, so the states don't depend on telling colors apart.
+        if selected != 0 && dashed_rim(pos) {
+            return material.background;
+        }
+        if over != 0 && stripe(pos, vec2(1.0, 1.0)) != stripe(pos, vec2(1.0, -1.0)) {
+            return material.background;
+        }
+        if solved != 0 && length(pos) < 0.4 && stripe(pos, vec2(1.0, 1.0)) {
+            color = vec4<f32>(color.rgb * 0.6, 1.0);
+        }
+    }
+
+    if solved == 0 && over == 0 && material.required != 0 {
+        if ring_gap(pos) {
+            return material.background;
+        }
+        if !ring_filled(pos) {
+            return vec4<f32>(mix(material.background.rgb, color.rgb, 0.25), 1.0);
+        }
     }
-    return vec4<f32>(1.0, 1.0, 1.0, 1.0) * solved_multiplier;
+    return color;
 }
@@ -0,0 +1,71 @@
+//! Compares obstacle checks through a grid with checks against every vertex, on levels of
+//! growing size. Run with `cargo bench`.
+
+use std::{f32::consts::PI, hint::black_box, time::Instant};
+
+use bevy::prelude::*;
+use graph_game::spatial::{
+    GRID_CELL_SIZE, MAX_EDGE_LEN, SpatialGrid, VERTEX_RADIUS, first_obstacle_pos,
+};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+const QUERIES: usize = 10_000;
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for vertex_count in [25, 100, 400, 1600] {
+        // Same density as generated levels.
+        let half_extent = 620.0 * (vertex_count as f32 / 25.0).sqrt();
+        let positions = (0..vertex_count)
+            .map(|_| {
+                Vec2::new(
+                    rng.gen_range(-half_extent..half_extent),
+                    rng.gen_range(-half_extent..half_extent),
+                )
+            })
+            .collect::<Vec<_>>();
+        let mut grid = SpatialGrid::new(GRID_CELL_SIZE);
+        for (i, pos) in positions.iter().enumerate() {
+            grid.insert(*pos, i);
+        }
+        let segments = (0..QUERIES)
+            .map(|_| {
+                let from = positions[rng.gen_range(0..vertex_count)];
+                let to = from
+                    + Vec2::from_angle(rng.gen_range(-PI..PI)) * rng.gen_range(0.0..MAX_EDGE_LEN);
+                (from, to)
+            })
+            .collect::<Vec<_>>();
+
+        let start = Instant::now();
+        for (from, to) in &segments {
+            black_box(first_obstacle_pos(
+                *from,
+                *to,
+                VERTEX_RADIUS,
+                MAX_EDGE_LEN,
+                positions.iter().copied(),
+            ));
+        }
+        let linear_time = start.elapsed();
+
+        let start = Instant::now();
+        for (from, to) in &segments {
+            let obstacles = grid
+                .near_segment(*from, *to, VERTEX_RADIUS)
+                .map(|(pos, _)| pos);
+            black_box(first_obstacle_pos(
+                *from,
+                *to,
+                VERTEX_RADIUS,
+                MAX_EDGE_LEN,
+                obstacles,
+            ));
+        }
+        let grid_time = start.elapsed();
+
+        println!(
+            "{vertex_count:>5} vertices: linear {linear_time:>9.2?}, grid {grid_time:>9.2?} for {QUERIES} queries",
+        );
+    }
+}
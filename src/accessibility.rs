@@ -0,0 +1,244 @@
+//! Describes the puzzle and menus to screen readers through AccessKit.
+
+use accesskit::{Live, Node, Role};
+use bevy::{
+    a11y::AccessibilityNode,
+    platform::collections::{HashMap, HashSet},
+    prelude::*,
+};
+
+use crate::{
+    GameState,
+    edge::{Edge, get_obstacle_pos},
+    level::Level,
+    locale::Locale,
+    vertex::{Vertex, VertexGrid},
+};
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(Startup, setup).add_systems(
+        Update,
+        (
+            number_vertices,
+            describe_vertices,
+            describe_level,
+            announce_changes,
+            announce_level_solved.run_if(state_changed::<GameState>),
+            label_ui_text,
+        )
+            .chain(),
+    );
+}
+
+/// Number of the vertex read out to screen readers, counted in reading order from 1.
+#[derive(Component)]
+struct VertexNumber(usize);
+
+/// Summary of the level, such as how many vertices are solved.
+#[derive(Component)]
+struct LevelDescription;
+
+/// Live region that screen readers read out whenever its label changes.
+#[derive(Component)]
+struct Announcer;
+
+fn setup(mut commands: Commands) {
+    commands.spawn((LevelDescription, AccessibilityNode(Node::new(Role::Label))));
+    let mut announcer = Node::new(Role::Status);
+    announcer.set_live(Live::Polite);
+    commands.spawn((Announcer, AccessibilityNode(announcer)));
+}
+
+/// Numbers the vertices of a new level from the top left to the bottom right.
+fn number_vertices(
+    new_vertex_q: Query<(), Added<Vertex>>,
+    vertex_q: Query<(Entity, &Transform), With<Vertex>>,
+    mut commands: Commands,
+) {
+    if new_vertex_q.is_empty() {
+        return;
+    }
+    let mut vertices = vertex_q.iter().collect::<Vec<_>>();
+    vertices.sort_by(|(_, a), (_, b)| {
+        let (a, b) = (a.translation, b.translation);
+        b.y.total_cmp(&a.y).then(a.x.total_cmp(&b.x))
+    });
+    for (i, (entity, _)) in vertices.into_iter().enumerate() {
+        commands.entity(entity).insert(VertexNumber(i + 1));
+    }
+}
+
+/// Lists each vertex's edges and the vertices it can still be connected to. Only rebuilt when
+/// edges change, a drag ends or the language changes, not on every frame of a drag or an
+/// animation.
+fn describe_vertices(
+    changed_q: Query<(), Changed<Vertex>>,
+    mut drag_end_evr: EventReader<Pointer<DragEnd>>,
+    vertex_q: Query<(Entity, &Vertex, &Transform, &VertexNumber)>,
+    grid: Res<VertexGrid>,
+    locale: Res<Locale>,
+    mut commands: Commands,
+) {
+    let mut drag_ended = false;
+    for ev in drag_end_evr.read() {
+        drag_ended |= vertex_q.contains(ev.target);
+    }
+    if changed_q.is_empty() && !drag_ended && !locale.is_changed() {
+        return;
+    }
+    let number = |entity| vertex_q.get(entity).map_or(0, |(_, _, _, number)| number.0);
+    let list = |mut numbers: Vec<usize>| {
+        numbers.sort();
+        numbers
+            .iter()
+            .map(usize::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    for (entity, vertex, transform, vertex_number) in &vertex_q {
+        let pos = transform.translation.xy();
+        let reachable = grid
+            .near(pos, Edge::MAX_LEN + Vertex::RADIUS * 2.0)
+            .filter_map(|other| vertex_q.get(other).ok())
+            .filter(|(other, _, other_transform, _)| {
+                let other_pos = other_transform.translation.xy();
+                let obstacles = grid
+                    .near_segment(pos, other_pos)
+                    .filter(|e| *e != entity && e != other)
+                    .filter_map(|e| vertex_q.get(e).ok())
+                    .map(|(_, _, transform, _)| transform.translation.xy());
+                *other != entity
+                    && !vertex.edges.contains_key(other)
+                    && pos.distance(other_pos) <= Edge::MAX_LEN + Vertex::RADIUS * 2.0
+                    && get_obstacle_pos(pos, other_pos, obstacles) == other_pos
+            })
+            .map(|(_, _, _, number)| number.0)
+            .collect();
+
+        let placed = vertex.edges.len();
+        let mut parts = vec![locale.format(
+            "a11y-vertex",
+            &[
+                ("number", &vertex_number.0),
+                ("placed", &placed),
+                ("required", &vertex.required_edges),
+            ],
+        )];
+        if placed == vertex.required_edges {
+            parts.push(locale.get("a11y-solved").to_string());
+        } else if placed > vertex.required_edges {
+            parts.push(locale.get("a11y-over").to_string());
+        }
+        if !vertex.edges.is_empty() {
+            let connected = vertex.edges.keys().map(|e| number(*e)).collect();
+            parts.push(locale.format("a11y-connected", &[("vertices", &list(connected))]));
+        }
+        if placed < vertex.required_edges {
+            parts.push(locale.format("a11y-reach", &[("vertices", &list(reachable))]));
+        }
+
+        let mut node = Node::new(Role::ListItem);
+        node.set_label(parts.join(". "));
+        commands.entity(entity).insert(AccessibilityNode(node));
+    }
+}
+
+fn describe_level(
+    vertex_q: Query<&Vertex>,
+    level: Res<Level>,
+    locale: Res<Locale>,
+    mut description: Single<&mut AccessibilityNode, With<LevelDescription>>,
+) {
+    let total = vertex_q.iter().count();
+    let solved = vertex_q
+        .iter()
+        .filter(|vertex| vertex.edges.len() == vertex.required_edges)
+        .count();
+    let label = locale.format(
+        "a11y-level",
+        &[("level", &level.0), ("solved", &solved), ("total", &total)],
+    );
+    if description.label() != Some(label.as_str()) {
+        description.set_label(label);
+    }
+}
+
+/// Announces placed and removed edges and newly solved vertices.
+#[allow(clippy::too_many_arguments)]
+fn announce_changes(
+    new_edge_q: Query<(Entity, &Edge), Added<Edge>>,
+    mut removed_edges: RemovedComponents<Edge>,
+    vertex_q: Query<(Entity, &Vertex, &VertexNumber)>,
+    mut edges: Local<HashMap<Entity, (Entity, Entity)>>,
+    mut solved: Local<HashSet<Entity>>,
+    state: Res<State<GameState>>,
+    locale: Res<Locale>,
+    mut announcer: Single<&mut AccessibilityNode, With<Announcer>>,
+) {
+    let number = |entity| vertex_q.get(entity).map_or(0, |(_, _, number)| number.0);
+    solved.retain(|entity| vertex_q.contains(*entity));
+    let mut parts = Vec::new();
+    for entity in removed_edges.read() {
+        if let Some((from, to)) = edges.remove(&entity) {
+            parts.push(locale.format(
+                "a11y-edge-removed",
+                &[("from", &number(from)), ("to", &number(to))],
+            ));
+        }
+    }
+    for (entity, edge) in &new_edge_q {
+        // Edges to `Entity::PLACEHOLDER` follow the cursor and aren't placed yet.
+        if edge.1 == Entity::PLACEHOLDER {
+            continue;
+        }
+        edges.insert(entity, (edge.0, edge.1));
+        parts.push(locale.format(
+            "a11y-edge-placed",
+            &[("from", &number(edge.0)), ("to", &number(edge.1))],
+        ));
+    }
+    for (entity, vertex, vertex_number) in &vertex_q {
+        if vertex.edges.len() != vertex.required_edges {
+            solved.remove(&entity);
+        } else if solved.insert(entity) && !vertex.edges.is_empty() {
+            parts.push(locale.format("a11y-vertex-solved", &[("number", &vertex_number.0)]));
+        }
+    }
+    // Edges are also removed when switching levels, which isn't worth announcing.
+    if *state.get() == GameState::Playing && !parts.is_empty() {
+        announcer.set_label(parts.join(". "));
+    }
+}
+
+fn announce_level_solved(
+    state: Res<State<GameState>>,
+    locale: Res<Locale>,
+    mut announcer: Single<&mut AccessibilityNode, With<Announcer>>,
+) {
+    if *state.get() == GameState::LevelExit {
+        announcer.set_label(locale.get("a11y-level-solved"));
+    }
+}
+
+/// Labels menu texts and buttons with their text, which Bevy only does for text in child
+/// entities.
+#[allow(clippy::type_complexity)]
+fn label_ui_text(
+    mut labeled_q: Query<
+        (&Text, &mut AccessibilityNode),
+        Or<(Changed<Text>, Changed<AccessibilityNode>)>,
+    >,
+    unlabeled_q: Query<(Entity, &Text), (Without<AccessibilityNode>, Without<Button>)>,
+    mut commands: Commands,
+) {
+    for (text, mut node) in &mut labeled_q {
+        if node.label() != Some(text.0.as_str()) {
+            node.set_label(text.0.as_str());
+        }
+    }
+    for (entity, text) in &unlabeled_q {
+        let mut node = Node::new(Role::Label);
+        node.set_label(text.0.as_str());
+        commands.entity(entity).insert(AccessibilityNode(node));
+    }
+}
@@ -1,206 +1,348 @@
-use std::{f32::consts::TAU, time::Duration};
+use std::{fs, path::Path};
 
 use bevy::{
-    audio::{AddAudioSource, Source},
+    audio::{AddAudioSource, SpatialScale, Volume},
+    ecs::system::SystemParam,
+    platform::collections::HashMap,
     prelude::*,
 };
-use rand::{Rng, SeedableRng, rngs::StdRng};
 
-pub fn plugin(app: &mut App) {
-    app.add_audio_source::<SelectAudio>()
-        .add_audio_source::<PlaceAudio>()
-        .add_audio_source::<BeatLevelAudio>()
-        .add_systems(Startup, setup);
-}
+use crate::{camera::CameraView, settings::Settings};
 
-#[derive(Resource)]
-pub struct SelectAudioHandle(pub Handle<SelectAudio>);
+use music::MusicKey;
+pub use synth::SynthAsset;
+use synth::{Envelope, Filter, Oscillator, Voice};
 
-#[derive(Resource)]
-pub struct PlaceAudioHandle(pub Handle<PlaceAudio>);
+mod music;
+mod synth;
+mod wav;
 
-#[derive(Resource)]
-pub struct BeatLevelAudioHandle(pub Handle<BeatLevelAudio>);
-
-fn setup(
-    mut commands: Commands,
-    mut select_audio: ResMut<Assets<SelectAudio>>,
-    mut place_audio: ResMut<Assets<PlaceAudio>>,
-    mut beat_level_audio: ResMut<Assets<BeatLevelAudio>>,
-) {
-    commands.insert_resource(SelectAudioHandle(select_audio.add(SelectAudio)));
-    commands.insert_resource(PlaceAudioHandle(place_audio.add(PlaceAudio)));
-    commands.insert_resource(BeatLevelAudioHandle(beat_level_audio.add(BeatLevelAudio)));
+pub fn plugin(app: &mut App) {
+    app.add_plugins(music::plugin)
+        .add_audio_source::<SynthAsset>()
+        .add_systems(Startup, setup);
 }
 
-const SAMPLE_RATE: u32 = 44100;
-
-#[derive(Asset, TypePath)]
-pub struct SelectAudio;
-
-impl Decodable for SelectAudio {
-    type DecoderItem = f32;
-    type Decoder = SelectDecoder;
+/// Volumes from 0 to 1.
+pub struct AudioSettings {
+    pub master: f32,
+    pub effects: f32,
+    pub music: f32,
+    pub muted: bool,
+}
 
-    fn decoder(&self) -> Self::Decoder {
-        SelectDecoder {
-            total_secs: 0.25,
-            progress: 0.0,
-            rng: StdRng::seed_from_u64(0),
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master: 1.0,
+            effects: 1.0,
+            music: 1.0,
+            muted: false,
         }
     }
 }
 
-pub struct SelectDecoder {
-    total_secs: f32,
-    progress: f32,
-    rng: StdRng,
-}
+impl AudioSettings {
+    pub fn effects_volume(&self) -> Volume {
+        self.volume(self.effects)
+    }
 
-impl Iterator for SelectDecoder {
-    type Item = f32;
+    pub fn music_volume(&self) -> Volume {
+        self.volume(self.music)
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        self.progress += 1.0 / self.total_secs / SAMPLE_RATE as f32;
-        if self.progress <= 1.0 {
-            let noise = self.rng.r#gen::<f32>() - 0.5;
-            let volume = (0.2_f32.powf(self.progress) - 0.2) * 0.15;
-            Some(noise * volume)
+    fn volume(&self, channel: f32) -> Volume {
+        if self.muted {
+            Volume::SILENT
         } else {
-            None
+            Volume::Linear(self.master * channel)
         }
     }
 }
 
-impl Source for SelectDecoder {
-    fn current_frame_len(&self) -> Option<usize> {
-        None
+/// Sound effects played with the volume from the settings.
+#[derive(SystemParam)]
+pub struct SoundEffects<'w> {
+    handles: Res<'w, SoundHandles>,
+    music_key: Res<'w, MusicKey>,
+    settings: Res<'w, Settings>,
+}
+
+impl SoundEffects<'_> {
+    pub fn select(&self, pos: Vec2) -> impl Bundle {
+        (
+            AudioPlayer(self.handle(Sound::Select)),
+            self.spatial_playback(),
+            Transform::from_translation(pos.extend(0.0)),
+        )
     }
 
-    fn channels(&self) -> u16 {
-        1
+    /// Placement sound, higher for vertices that require more edges.
+    pub fn place(&self, pos: Vec2, required_edges: usize) -> impl Bundle {
+        let pitch = pentatonic_pitch(required_edges.saturating_sub(1));
+        (
+            AudioPlayer(self.handle(Sound::Place)),
+            self.spatial_playback().with_speed(pitch),
+            Transform::from_translation(pos.extend(0.0)),
+        )
     }
 
-    fn sample_rate(&self) -> u32 {
-        SAMPLE_RATE
+    pub fn vertex_solved(&self, pos: Vec2) -> impl Bundle {
+        (
+            AudioPlayer(self.handle(Sound::VertexSolved)),
+            self.spatial_playback(),
+            Transform::from_translation(pos.extend(0.0)),
+        )
     }
 
-    fn total_duration(&self) -> Option<Duration> {
-        Some(Duration::from_secs_f32(self.total_secs))
+    pub fn remove(&self, pos: Vec2) -> impl Bundle {
+        (
+            AudioPlayer(self.handle(Sound::Remove)),
+            self.spatial_playback(),
+            Transform::from_translation(pos.extend(0.0)),
+        )
     }
-}
 
-#[derive(Asset, TypePath)]
-pub struct PlaceAudio;
+    /// Level beaten sound, in the key of the music so that it resolves it.
+    pub fn beat_level(&self, playback: PlaybackSettings) -> impl Bundle {
+        (
+            AudioPlayer(self.handle(Sound::BeatLevel)),
+            self.playback(playback).with_speed(self.music_key.pitch()),
+        )
+    }
 
-impl Decodable for PlaceAudio {
-    type DecoderItem = f32;
-    type Decoder = PlaceDecoder;
+    fn handle(&self, sound: Sound) -> Handle<SynthAsset> {
+        self.handles.0[&sound].clone()
+    }
 
-    fn decoder(&self) -> Self::Decoder {
-        PlaceDecoder {
-            total_secs: 0.5,
-            progress: 0.0,
-            rng: StdRng::seed_from_u64(0),
-        }
+    fn playback(&self, playback: PlaybackSettings) -> PlaybackSettings {
+        playback.with_volume(self.settings.audio.effects_volume())
+    }
+
+    /// Playback that pans the sound by its position relative to the camera's
+    /// [`SpatialListener`].
+    fn spatial_playback(&self) -> PlaybackSettings {
+        self.playback(PlaybackSettings::DESPAWN)
+            .with_spatial(true)
+            .with_spatial_scale(SpatialScale::new_2d(1.0 / LISTENER_GAP))
     }
 }
 
-pub struct PlaceDecoder {
-    total_secs: f32,
-    progress: f32,
-    rng: StdRng,
+/// Distance between the ears of the camera's [`SpatialListener`]. Sounds this far apart are
+/// panned from one side to the other.
+pub const LISTENER_GAP: f32 = CameraView::MIN_VIEW_SIZE;
+
+/// Every sound effect. The game, `--export-sounds` and the tests all go through this list.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Sound {
+    Select,
+    Place,
+    BeatLevel,
+    VertexSolved,
+    Remove,
 }
 
-impl Iterator for PlaceDecoder {
-    type Item = f32;
+impl Sound {
+    const ALL: [Self; 5] = [
+        Self::Select,
+        Self::Place,
+        Self::BeatLevel,
+        Self::VertexSolved,
+        Self::Remove,
+    ];
+
+    /// Name of the exported file.
+    fn key(self) -> &'static str {
+        match self {
+            Self::Select => "select",
+            Self::Place => "place",
+            Self::BeatLevel => "beat_level",
+            Self::VertexSolved => "vertex_solved",
+            Self::Remove => "remove",
+        }
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        self.progress += 1.0 / self.total_secs / SAMPLE_RATE as f32;
-        if self.progress <= 1.0 {
-            let noise = self.rng.r#gen::<f32>() - 0.5;
-            let volume = 0.05_f32.powf(self.progress) * 0.2;
-            Some(noise * volume)
-        } else {
-            None
+    fn asset(self) -> SynthAsset {
+        match self {
+            Self::Select => SynthAsset::select(),
+            Self::Place => SynthAsset::place(),
+            Self::BeatLevel => SynthAsset::beat_level(),
+            Self::VertexSolved => SynthAsset::vertex_solved(),
+            Self::Remove => SynthAsset::remove(),
         }
     }
 }
 
-impl Source for PlaceDecoder {
-    fn current_frame_len(&self) -> Option<usize> {
-        None
-    }
+#[derive(Resource)]
+struct SoundHandles(HashMap<Sound, Handle<SynthAsset>>);
+
+fn setup(mut commands: Commands, mut synth_assets: ResMut<Assets<SynthAsset>>) {
+    commands.insert_resource(SoundHandles(
+        Sound::ALL
+            .into_iter()
+            .map(|sound| (sound, synth_assets.add(sound.asset())))
+            .collect(),
+    ));
+}
 
-    fn channels(&self) -> u16 {
-        1
-    }
+/// Speed that shifts a sound up by `step` notes of the major pentatonic scale.
+fn pentatonic_pitch(step: usize) -> f32 {
+    const SEMITONES: [usize; 5] = [0, 2, 4, 7, 9];
+    let semitones = SEMITONES[step % 5] + 12 * (step / 5);
+    2.0_f32.powf(semitones as f32 / 12.0)
+}
 
-    fn sample_rate(&self) -> u32 {
-        SAMPLE_RATE
-    }
+const SAMPLE_RATE: u32 = 44100;
 
-    fn total_duration(&self) -> Option<Duration> {
-        Some(Duration::from_secs_f32(self.total_secs))
+/// Renders every sound effect to a WAV file in `dir` and prints its length and peak.
+pub fn export_sounds(dir: &Path) -> AppExit {
+    if let Err(e) = fs::create_dir_all(dir) {
+        eprintln!("Failed to create {}: {e}", dir.display());
+        return AppExit::error();
     }
+    for sound in Sound::ALL {
+        let samples = sound.asset().decoder().collect::<Vec<_>>();
+        let path = dir.join(format!("{}.wav", sound.key()));
+        if let Err(e) = wav::write_wav(&path, &samples, SAMPLE_RATE) {
+            eprintln!("Failed to write {}: {e}", path.display());
+            return AppExit::error();
+        }
+        let peak = samples.iter().fold(0.0_f32, |peak, s| peak.max(s.abs()));
+        let clipped = samples.iter().filter(|s| s.abs() > 1.0).count();
+        println!(
+            "{}: {:.2} s, peak {peak:.3}, {clipped} clipped samples",
+            path.display(),
+            samples.len() as f32 / SAMPLE_RATE as f32,
+        );
+    }
+    AppExit::Success
 }
 
-#[derive(Asset, TypePath)]
-pub struct BeatLevelAudio;
-
-impl Decodable for BeatLevelAudio {
-    type DecoderItem = f32;
-    type Decoder = BeatLevelDecoder;
-
-    fn decoder(&self) -> Self::Decoder {
-        BeatLevelDecoder {
-            total_secs: 1.0,
-            hz: vec![220.0, 440.0, 880.0],
-            progress: 0.0,
+/// The sound effects, defined in code. Synth sounds can't be loaded from asset files yet.
+impl SynthAsset {
+    /// Short noise burst when selecting a vertex.
+    fn select() -> Self {
+        Self {
+            voices: vec![Voice {
+                oscillator: Oscillator::Noise { seed: 0 },
+                envelope: Envelope::decay(0.25, 0.2),
+                gain: 0.06,
+                filter: None,
+                delay: 0.0,
+            }],
+            ..default()
         }
     }
-}
 
-pub struct BeatLevelDecoder {
-    total_secs: f32,
-    hz: Vec<f32>,
-    progress: f32,
-}
+    /// Noise burst with a short tone when placing an edge.
+    fn place() -> Self {
+        Self {
+            voices: vec![
+                Voice {
+                    oscillator: Oscillator::Noise { seed: 0 },
+                    envelope: Envelope::decay(0.5, 0.05),
+                    gain: 0.1,
+                    filter: None,
+                    delay: 0.0,
+                },
+                Voice {
+                    oscillator: Oscillator::Sine { hz: 330.0 },
+                    envelope: Envelope::decay(0.3, 0.1),
+                    gain: 0.15,
+                    filter: None,
+                    delay: 0.0,
+                },
+            ],
+            ..default()
+        }
+    }
 
-impl Iterator for BeatLevelDecoder {
-    type Item = f32;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        self.progress += 1.0 / self.total_secs / SAMPLE_RATE as f32;
-        if self.progress <= 1.0 {
-            let value = self
-                .hz
-                .iter()
-                .map(|hz| (hz * self.progress * self.total_secs * TAU).sin())
-                .sum::<f32>()
-                / self.hz.len() as f32;
-            let volume = (0.5_f32.powf(self.progress) - 0.5) * 2.0;
-            Some(value * volume)
-        } else {
-            None
+    /// Major chord when a vertex gets all of its edges.
+    fn vertex_solved() -> Self {
+        Self {
+            voices: [523.25, 659.25, 783.99]
+                .into_iter()
+                .map(|hz| Voice {
+                    oscillator: Oscillator::Square { hz },
+                    envelope: Envelope {
+                        attack: 0.01,
+                        ..Envelope::decay(0.6, 0.2)
+                    },
+                    gain: 0.05,
+                    filter: Some(Filter::LowPass { cutoff_hz: 2000.0 }),
+                    delay: 0.0,
+                })
+                .collect(),
+            ..default()
         }
     }
-}
 
-impl Source for BeatLevelDecoder {
-    fn current_frame_len(&self) -> Option<usize> {
-        None
+    /// Descending tone when removing an edge.
+    fn remove() -> Self {
+        Self {
+            voices: vec![Voice {
+                oscillator: Oscillator::Glide {
+                    from_hz: 440.0,
+                    to_hz: 220.0,
+                },
+                envelope: Envelope::decay(0.3, 0.3),
+                gain: 0.2,
+                filter: None,
+                delay: 0.0,
+            }],
+            ..default()
+        }
     }
 
-    fn channels(&self) -> u16 {
-        1
+    /// Octaves of A when a level is beaten.
+    fn beat_level() -> Self {
+        Self {
+            voices: [220.0, 440.0, 880.0]
+                .into_iter()
+                .map(|hz| Voice {
+                    oscillator: Oscillator::Sine { hz },
+                    envelope: Envelope::decay(1.0, 0.5),
+                    gain: 1.0 / 3.0,
+                    filter: None,
+                    delay: 0.0,
+                })
+                .collect(),
+            ..default()
+        }
     }
+}
 
-    fn sample_rate(&self) -> u32 {
-        SAMPLE_RATE
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Number of samples each sound is expected to have, and the range its loudest sample is
+    /// expected in, so changes that make a sound longer, shorter, quieter or louder don't go
+    /// unnoticed.
+    fn expected(sound: Sound) -> (usize, f32, f32) {
+        match sound {
+            Sound::Select => (11025, 0.03, 0.1),
+            Sound::Place => (22050, 0.15, 0.35),
+            Sound::BeatLevel => (44100, 0.5, 1.0),
+            Sound::VertexSolved => (26901, 0.08, 0.25),
+            Sound::Remove => (13230, 0.1, 0.3),
+        }
     }
 
-    fn total_duration(&self) -> Option<Duration> {
-        Some(Duration::from_secs_f32(self.total_secs))
+    #[test]
+    fn sounds_have_expected_length_and_peak() {
+        for sound in Sound::ALL {
+            let name = sound.key();
+            let samples = sound.asset().decoder().collect::<Vec<_>>();
+            let (len, min, max) = expected(sound);
+            assert_eq!(samples.len(), len, "{name} has the wrong number of samples");
+
+            let peak = samples.iter().fold(0.0_f32, |peak, s| peak.max(s.abs()));
+            assert!(
+                (min..=max).contains(&peak),
+                "{name} peaks at {peak}, expected {min} to {max}",
+            );
+            assert!(peak <= 1.0, "{name} clips at {peak}");
+        }
     }
 }
@@ -0,0 +1,170 @@
+use bevy::{audio::Volume, prelude::*};
+use rand::{Rng, SeedableRng, rngs::StdRng, seq::SliceRandom};
+
+use super::synth::{Envelope, Filter, Oscillator, SynthAsset, Voice};
+use crate::{GameState, level::Level, settings::Settings, vertex::Vertex};
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<MusicKey>().add_systems(
+        Update,
+        (
+            start_music.run_if(resource_changed::<Level>),
+            update_music_volume,
+        )
+            .chain(),
+    );
+}
+
+/// Key of the current music, in semitones above A.
+#[derive(Resource, Default)]
+pub struct MusicKey(pub i32);
+
+impl MusicKey {
+    /// Speed that transposes a sound in A into this key.
+    pub fn pitch(&self) -> f32 {
+        2.0_f32.powf(self.0 as f32 / 12.0)
+    }
+}
+
+/// A looping part of the music that fades in once enough of the level is solved.
+#[derive(Component)]
+struct MusicLayer {
+    /// Fraction of solved vertices at which the layer is fully faded in.
+    full_at: f32,
+    /// Current volume from 0 to 1, without the volume settings.
+    volume: f32,
+}
+
+/// Length of one chord.
+const BAR_SECS: f32 = 2.4;
+const BARS: usize = 4;
+
+const MAJOR: [i32; 7] = [0, 2, 4, 5, 7, 9, 11];
+const MINOR: [i32; 7] = [0, 2, 3, 5, 7, 8, 10];
+
+/// Chord progressions as scale degrees, starting from 0.
+const PROGRESSIONS: [[usize; BARS]; 4] = [[0, 4, 5, 3], [0, 5, 3, 4], [5, 3, 0, 4], [0, 3, 5, 4]];
+
+/// Builds the music for `level` and replaces the music of the previous level.
+fn start_music(
+    layer_q: Query<Entity, With<MusicLayer>>,
+    mut synth_assets: ResMut<Assets<SynthAsset>>,
+    mut key: ResMut<MusicKey>,
+    level: Res<Level>,
+    mut commands: Commands,
+) {
+    for entity in &layer_q {
+        commands.entity(entity).despawn();
+    }
+
+    let mut rng = StdRng::seed_from_u64(level.0);
+    key.0 = rng.gen_range(-5..=6);
+    let scale = if rng.r#gen() { MAJOR } else { MINOR };
+    let progression = PROGRESSIONS.choose(&mut rng).unwrap();
+
+    // Semitones above A of the notes of the chord on `degree`.
+    let chord = |degree: usize| {
+        [degree, degree + 2, degree + 4].map(|step| scale[step % 7] + 12 * (step / 7) as i32)
+    };
+    let hz = |octave_hz: f32, semitones: i32| {
+        octave_hz * 2.0_f32.powf((key.0 + semitones) as f32 / 12.0)
+    };
+
+    let mut pad = Vec::new();
+    let mut bass = Vec::new();
+    let mut arpeggio = Vec::new();
+    for (bar, degree) in progression.iter().enumerate() {
+        let start = bar as f32 * BAR_SECS;
+        let notes = chord(*degree);
+        for note in notes {
+            pad.push(Voice {
+                oscillator: Oscillator::Sine {
+                    hz: hz(220.0, note),
+                },
+                envelope: Envelope {
+                    attack: 0.8,
+                    decay: 0.4,
+                    sustain: 0.7,
+                    hold: BAR_SECS - 1.8,
+                    release: 0.6,
+                    curve: 1.0,
+                },
+                gain: 0.05,
+                filter: None,
+                delay: start,
+            });
+        }
+        for beat in 0..2 {
+            bass.push(Voice {
+                oscillator: Oscillator::Sine {
+                    hz: hz(55.0, notes[0]),
+                },
+                envelope: Envelope::decay(BAR_SECS / 2.0, 0.2),
+                gain: 0.2,
+                filter: None,
+                delay: start + beat as f32 * BAR_SECS / 2.0,
+            });
+        }
+        let steps = 8;
+        let pattern = [0, 1, 2, 1, 2, 0, 1, 2];
+        for step in 0..steps {
+            arpeggio.push(Voice {
+                oscillator: Oscillator::Square {
+                    hz: hz(440.0, notes[pattern[step]]),
+                },
+                envelope: Envelope::decay(BAR_SECS / steps as f32, 0.1),
+                gain: 0.03,
+                filter: Some(Filter::LowPass { cutoff_hz: 1500.0 }),
+                delay: start + step as f32 * BAR_SECS / steps as f32,
+            });
+        }
+    }
+
+    for (voices, full_at) in [(pad, 0.0), (bass, 0.25), (arpeggio, 0.6)] {
+        let asset = synth_assets.add(SynthAsset {
+            voices,
+            min_secs: BARS as f32 * BAR_SECS,
+        });
+        commands.spawn((
+            MusicLayer {
+                full_at,
+                volume: 0.0,
+            },
+            AudioPlayer(asset),
+            PlaybackSettings::LOOP.with_volume(Volume::SILENT),
+        ));
+    }
+}
+
+/// Fades layers in as more vertices are solved and out when the level is beaten.
+fn update_music_volume(
+    mut layer_q: Query<(&mut MusicLayer, &mut AudioSink)>,
+    vertex_q: Query<&Vertex>,
+    state: Res<State<GameState>>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+) {
+    let total = vertex_q.iter().count();
+    let solved = vertex_q
+        .iter()
+        .filter(|vertex| vertex.edges.len() == vertex.required_edges)
+        .count();
+    let solved_fraction = if total == 0 {
+        0.0
+    } else {
+        solved as f32 / total as f32
+    };
+    let level_beaten = *state.get() == GameState::LevelExit;
+
+    const FADE_PER_SEC: f32 = 0.5;
+    for (mut layer, mut sink) in &mut layer_q {
+        let target = if level_beaten {
+            0.0
+        } else {
+            ((solved_fraction - layer.full_at) * 4.0 + 1.0).clamp(0.0, 1.0)
+        };
+        let max_step = FADE_PER_SEC * time.delta_secs();
+        layer.volume += (target - layer.volume).clamp(-max_step, max_step);
+        sink.set_volume(settings.audio.music_volume() * Volume::Linear(layer.volume));
+    }
+}
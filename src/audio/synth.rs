@@ -0,0 +1,238 @@
+use std::{f32::consts::TAU, time::Duration};
+
+use bevy::{audio::Source, prelude::*};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use super::SAMPLE_RATE;
+
+/// A sound made of voices that are mixed together.
+#[derive(Asset, TypePath, Clone, Default)]
+pub struct SynthAsset {
+    pub voices: Vec<Voice>,
+    /// Minimum length in seconds. Shorter sounds are padded with silence, e.g. to keep loops in
+    /// sync.
+    pub min_secs: f32,
+}
+
+impl SynthAsset {
+    /// Time until the last voice ends, at least `min_secs`.
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f32(
+            self.voices
+                .iter()
+                .map(|voice| voice.delay + voice.envelope.duration())
+                .fold(self.min_secs, f32::max),
+        )
+    }
+}
+
+impl Decodable for SynthAsset {
+    type DecoderItem = f32;
+    type Decoder = SynthDecoder;
+
+    fn decoder(&self) -> Self::Decoder {
+        SynthDecoder {
+            voices: self
+                .voices
+                .iter()
+                .map(|voice| VoiceState {
+                    rng: StdRng::seed_from_u64(match voice.oscillator {
+                        Oscillator::Noise { seed } => seed,
+                        _ => 0,
+                    }),
+                    voice: voice.clone(),
+                    filtered: 0.0,
+                })
+                .collect(),
+            duration: self.duration(),
+            total_samples: (self.duration().as_secs_f32() * SAMPLE_RATE as f32) as u32,
+            sample: 0,
+        }
+    }
+}
+
+/// An oscillator with an envelope, a gain and an optional filter.
+#[derive(Clone)]
+pub struct Voice {
+    pub oscillator: Oscillator,
+    pub envelope: Envelope,
+    pub gain: f32,
+    pub filter: Option<Filter>,
+    /// Seconds from the start of the sound until the voice starts.
+    pub delay: f32,
+}
+
+/// Waveform from -1 to 1.
+#[derive(Clone)]
+pub enum Oscillator {
+    Sine {
+        hz: f32,
+    },
+    Square {
+        hz: f32,
+    },
+    /// Sine that slides exponentially from `from_hz` to `to_hz` over the length of the voice.
+    Glide {
+        from_hz: f32,
+        to_hz: f32,
+    },
+    Noise {
+        seed: u64,
+    },
+}
+
+/// ADSR envelope, with times in seconds.
+#[derive(Clone)]
+pub struct Envelope {
+    pub attack: f32,
+    pub decay: f32,
+    /// Level held after the decay, from 0 to 1.
+    pub sustain: f32,
+    /// Time the sustain level is held.
+    pub hold: f32,
+    pub release: f32,
+    /// Shape of the decay and release. 1 is linear; smaller values fall faster at the start,
+    /// like `curve.powf(t)`.
+    pub curve: f32,
+}
+
+impl Envelope {
+    /// An envelope that starts at full volume and fades out over `secs`.
+    pub fn decay(secs: f32, curve: f32) -> Self {
+        Self {
+            attack: 0.0,
+            decay: secs,
+            sustain: 0.0,
+            hold: 0.0,
+            release: 0.0,
+            curve,
+        }
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.attack + self.decay + self.hold + self.release
+    }
+
+    /// Level at `t` seconds, from 0 to 1.
+    pub fn level(&self, t: f32) -> f32 {
+        let mut t = t;
+        if t < self.attack {
+            return t / self.attack;
+        }
+        t -= self.attack;
+        if t < self.decay {
+            return self.sustain + (1.0 - self.sustain) * self.fall(t / self.decay);
+        }
+        t -= self.decay;
+        if t < self.hold {
+            return self.sustain;
+        }
+        t -= self.hold;
+        if t < self.release {
+            return self.sustain * self.fall(t / self.release);
+        }
+        0.0
+    }
+
+    /// Falls from 1 to 0 as `x` goes from 0 to 1.
+    fn fall(&self, x: f32) -> f32 {
+        if (self.curve - 1.0).abs() < f32::EPSILON {
+            1.0 - x
+        } else {
+            (self.curve.powf(x) - self.curve) / (1.0 - self.curve)
+        }
+    }
+}
+
+/// One-pole filter.
+#[derive(Clone)]
+pub enum Filter {
+    LowPass { cutoff_hz: f32 },
+}
+
+impl Filter {
+    /// Smoothing factor of the filter at [`SAMPLE_RATE`].
+    fn alpha(cutoff_hz: f32) -> f32 {
+        let rc = 1.0 / (TAU * cutoff_hz);
+        let dt = 1.0 / SAMPLE_RATE as f32;
+        dt / (rc + dt)
+    }
+}
+
+struct VoiceState {
+    voice: Voice,
+    rng: StdRng,
+    /// Previous output of the filter.
+    filtered: f32,
+}
+
+impl VoiceState {
+    fn sample(&mut self, t: f32) -> f32 {
+        let t = t - self.voice.delay;
+        if t < 0.0 {
+            return 0.0;
+        }
+        let raw = match self.voice.oscillator {
+            Oscillator::Sine { hz } => (hz * t * TAU).sin(),
+            Oscillator::Square { hz } => (hz * t * TAU).sin().signum(),
+            Oscillator::Glide { from_hz, to_hz } => {
+                // Integral of `from_hz * ratio.powf(t / duration)`.
+                let duration = self.voice.envelope.duration();
+                let ln_ratio = (to_hz / from_hz).ln();
+                let cycles = if ln_ratio.abs() < f32::EPSILON {
+                    from_hz * t
+                } else {
+                    from_hz * duration * ((ln_ratio * t / duration).exp() - 1.0) / ln_ratio
+                };
+                (cycles * TAU).sin()
+            }
+            Oscillator::Noise { .. } => self.rng.r#gen::<f32>() * 2.0 - 1.0,
+        };
+        let value = match self.voice.filter {
+            None => raw,
+            Some(Filter::LowPass { cutoff_hz }) => {
+                self.filtered += Filter::alpha(cutoff_hz) * (raw - self.filtered);
+                self.filtered
+            }
+        };
+        value * self.voice.envelope.level(t) * self.voice.gain
+    }
+}
+
+pub struct SynthDecoder {
+    voices: Vec<VoiceState>,
+    duration: Duration,
+    total_samples: u32,
+    sample: u32,
+}
+
+impl Iterator for SynthDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.sample >= self.total_samples {
+            return None;
+        }
+        let t = self.sample as f32 / SAMPLE_RATE as f32;
+        self.sample += 1;
+        Some(self.voices.iter_mut().map(|voice| voice.sample(t)).sum())
+    }
+}
+
+impl Source for SynthDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        Some(self.duration)
+    }
+}
@@ -0,0 +1,134 @@
+//! Printable puzzle books: pages of generated puzzles followed by their solutions.
+
+use std::{fs, io, path::Path};
+
+use bevy::prelude::*;
+
+use crate::{export::PuzzleDrawing, level::Puzzle};
+
+/// Size of a page in millimeters, which is A4.
+const PAGE_SIZE: Vec2 = Vec2::new(210.0, 297.0);
+const PAGE_MARGIN: f32 = 15.0;
+/// Solutions are drawn smaller than the puzzles, this many to a page.
+const SOLUTIONS_PER_PAGE: UVec2 = UVec2::new(2, 3);
+/// Printed at the bottom of every solution page.
+const SOLUTIONS_NOTE: &str =
+    "These are the generated solutions. Other solutions may also be valid.";
+
+pub const USAGE: &str = "Usage: --book COUNT LEVEL DIR
+Writes COUNT puzzles as difficult as LEVEL to DIR as SVG pages, followed by their solutions.
+The solutions are the edges the puzzles were generated from and may not be the only ones.";
+
+/// Seeds of book puzzles start at `level` times this. Levels start at 1, so the seeds differ
+/// from the ones of the levels in the game, which are the level numbers.
+const SEED_STRIDE: u64 = 1_000_000;
+
+/// Writes a book of `count` puzzles as difficult as `level` to `dir`, one SVG per page. Run
+/// with `--book COUNT LEVEL DIR`. The same arguments always give the same book.
+pub fn write_book(count: usize, level: u64, dir: &Path) -> AppExit {
+    // More puzzles would reuse the seeds of the next level's books.
+    if count as u64 >= SEED_STRIDE {
+        eprintln!("COUNT must be below {SEED_STRIDE}");
+        eprintln!("{USAGE}");
+        return AppExit::error();
+    }
+    // The last seed has to fit into a `u64`.
+    let max_level = (u64::MAX - count as u64) / SEED_STRIDE;
+    if !(1..=max_level).contains(&level) {
+        eprintln!("LEVEL must be from 1 to {max_level}");
+        eprintln!("{USAGE}");
+        return AppExit::error();
+    }
+    let first_seed = level * SEED_STRIDE;
+    let puzzles = (0..count as u64)
+        .map(|i| {
+            let seed = first_seed + i;
+            (seed, Puzzle::generate_seeded(level, seed))
+        })
+        .collect::<Vec<_>>();
+
+    match write_pages(&puzzles, dir) {
+        Ok(pages) => {
+            println!("Wrote {pages} pages to {}", dir.display());
+            AppExit::Success
+        }
+        Err(e) => {
+            eprintln!("Failed to write book to {}: {e}", dir.display());
+            AppExit::error()
+        }
+    }
+}
+
+/// Writes a page for each puzzle and then the solution pages, returning the number of pages.
+fn write_pages(puzzles: &[(u64, Puzzle)], dir: &Path) -> io::Result<usize> {
+    fs::create_dir_all(dir)?;
+    let content = Rect::from_corners(Vec2::splat(PAGE_MARGIN), PAGE_SIZE - PAGE_MARGIN);
+    let mut pages = Vec::new();
+    for (i, (seed, puzzle)) in puzzles.iter().enumerate() {
+        let title = format!("Puzzle {} (seed {seed})", i + 1);
+        let drawing = PuzzleDrawing::from_puzzle(puzzle, title, false);
+        pages.push(drawing.to_nested_svg(content));
+    }
+
+    let cell_size = content.size() / SOLUTIONS_PER_PAGE.as_vec2();
+    let per_page = (SOLUTIONS_PER_PAGE.x * SOLUTIONS_PER_PAGE.y) as usize;
+    for (page, chunk) in puzzles.chunks(per_page).enumerate() {
+        let mut svg = String::new();
+        for (j, (_, puzzle)) in chunk.iter().enumerate() {
+            let i = page * per_page + j;
+            let drawing = PuzzleDrawing::from_puzzle(puzzle, format!("Solution {}", i + 1), true);
+            let cell = UVec2::new(
+                j as u32 % SOLUTIONS_PER_PAGE.x,
+                j as u32 / SOLUTIONS_PER_PAGE.x,
+            );
+            let min = content.min + cell.as_vec2() * cell_size;
+            svg += &drawing.to_nested_svg(Rect::from_corners(min, min + cell_size));
+        }
+        svg += &format!(
+            "<text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" font-size=\"4\" text-anchor=\"middle\">{SOLUTIONS_NOTE}</text>\n",
+            PAGE_SIZE.x / 2.0,
+            PAGE_SIZE.y - PAGE_MARGIN / 2.0,
+        );
+        pages.push(svg);
+    }
+
+    for (i, page) in pages.iter().enumerate() {
+        fs::write(
+            dir.join(format!("page-{:03}.svg", i + 1)),
+            format!(
+                "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {w} {h}\" width=\"{w}mm\" height=\"{h}mm\">\n\
+                <rect width=\"{w}\" height=\"{h}\" fill=\"white\"/>\n\
+                {page}</svg>\n",
+                w = PAGE_SIZE.x,
+                h = PAGE_SIZE.y,
+            ),
+        )?;
+    }
+    Ok(pages.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_gives_same_puzzle() {
+        assert_eq!(
+            Puzzle::generate_seeded(30, 30_000_000),
+            Puzzle::generate_seeded(30, 30_000_000),
+        );
+    }
+
+    #[test]
+    fn book_has_puzzle_and_solution_pages() {
+        let dir = std::env::temp_dir().join("graph_game_book_test");
+        for count in [1, 6, 7] {
+            let puzzles = (0..count)
+                .map(|seed| (seed, Puzzle::generate_seeded(5, seed)))
+                .collect::<Vec<_>>();
+            let pages = write_pages(&puzzles, &dir).unwrap();
+            assert_eq!(pages, count as usize + (count as usize).div_ceil(6));
+        }
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,121 @@
+use bevy::{
+    input::{
+        gestures::PinchGesture,
+        mouse::{AccumulatedMouseMotion, AccumulatedMouseScroll, MouseScrollUnit},
+    },
+    prelude::*,
+    window::PrimaryWindow,
+};
+
+use crate::GameState;
+
+pub fn plugin(app: &mut App) {
+    app.insert_resource(CameraView::framing(Rect::from_center_half_size(
+        Vec2::ZERO,
+        Vec2::splat(CameraView::MIN_VIEW_SIZE / 2.0),
+    )))
+    .add_systems(
+        Update,
+        (handle_zoom, handle_pan).run_if(in_state(GameState::Playing)),
+    )
+    .add_systems(
+        Update,
+        apply_zoom.run_if(not(in_state(GameState::LevelSelect))),
+    )
+    .add_systems(OnEnter(GameState::LevelSelect), reset_camera)
+    .add_systems(OnExit(GameState::LevelSelect), apply_center);
+}
+
+/// The part of the level the camera shows while playing.
+#[derive(Resource)]
+pub struct CameraView {
+    pub center: Vec2,
+    pub zoom: f32,
+    /// Area the center is allowed to move in.
+    bounds: Rect,
+    max_zoom: f32,
+}
+
+impl CameraView {
+    /// Size of the area that is visible at zoom 1.
+    pub const MIN_VIEW_SIZE: f32 = 1440.0;
+    const MIN_ZOOM: f32 = 0.5;
+
+    /// Creates a view that shows all of `extent`.
+    pub fn framing(extent: Rect) -> Self {
+        let max_zoom = (extent.size().max_element() / Self::MIN_VIEW_SIZE).max(1.0);
+        Self {
+            center: extent.center(),
+            zoom: max_zoom,
+            bounds: extent,
+            max_zoom,
+        }
+    }
+
+    fn set_zoom(&mut self, zoom: f32) {
+        self.zoom = zoom.clamp(Self::MIN_ZOOM, self.max_zoom);
+    }
+
+    fn set_center(&mut self, center: Vec2) {
+        self.center = center.clamp(self.bounds.min, self.bounds.max);
+    }
+}
+
+fn handle_zoom(
+    scroll: Res<AccumulatedMouseScroll>,
+    mut pinch_evr: EventReader<PinchGesture>,
+    mut view: ResMut<CameraView>,
+) {
+    let lines = match scroll.unit {
+        MouseScrollUnit::Line => scroll.delta.y,
+        MouseScrollUnit::Pixel => scroll.delta.y / 100.0,
+    };
+    let mut factor = 1.1_f32.powf(-lines);
+    for pinch in pinch_evr.read() {
+        factor /= 1.0 + pinch.0;
+    }
+    if factor != 1.0 {
+        let zoom = view.zoom * factor;
+        view.set_zoom(zoom);
+    }
+}
+
+fn handle_pan(
+    mouse: Res<ButtonInput<MouseButton>>,
+    motion: Res<AccumulatedMouseMotion>,
+    mut view: ResMut<CameraView>,
+    cam: Single<(&mut Transform, &Projection), With<Camera>>,
+    window: Single<&Window, With<PrimaryWindow>>,
+) {
+    let (mut transform, projection) = cam.into_inner();
+    if mouse.pressed(MouseButton::Middle) && motion.delta != Vec2::ZERO {
+        let Projection::Orthographic(ortho) = projection else {
+            return;
+        };
+        let world_per_pixel = ortho.area.width() / window.width();
+        let center = view.center + Vec2::new(-motion.delta.x, motion.delta.y) * world_per_pixel;
+        view.set_center(center);
+    }
+    transform.translation = view.center.extend(transform.translation.z);
+}
+
+fn apply_zoom(view: Res<CameraView>, mut projection: Single<&mut Projection, With<Camera>>) {
+    if let Projection::Orthographic(ortho) = &mut **projection
+        && ortho.scale != view.zoom
+    {
+        ortho.scale = view.zoom;
+    }
+}
+
+/// Shows the origin at zoom 1, where the level select is drawn.
+fn reset_camera(cam: Single<(&mut Transform, &mut Projection), With<Camera>>) {
+    let (mut transform, mut projection) = cam.into_inner();
+    transform.translation = Vec3::ZERO;
+    if let Projection::Orthographic(ortho) = &mut *projection {
+        ortho.scale = 1.0;
+    }
+}
+
+fn apply_center(view: Res<CameraView>, mut transform: Single<&mut Transform, With<Camera>>) {
+    transform.translation = view.center.extend(transform.translation.z);
+}
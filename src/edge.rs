@@ -1,92 +1,362 @@
 use bevy::{
-    math::bounding::{BoundingCircle, RayCast2d},
     prelude::*,
+    render::render_resource::{AsBindGroup, ShaderRef},
+    sprite::{AlphaMode2d, Material2d, Material2dPlugin},
 };
 
 use crate::{
     GameState,
-    level::CheckIfSolvedSystem,
-    vertex::{Selected, Vertex, VertexMaterial},
+    audio::SoundEffects,
+    effects,
+    input::{Action, action_just_pressed},
+    level::{CheckIfSolvedSystem, LevelSolution, LevelStats},
+    meshes::SharedMeshes,
+    settings::Settings,
+    spatial::{self, first_obstacle_pos},
+    theme::Palette,
+    vertex::{Selected, Vertex, VertexGrid, VertexMaterial},
 };
 
 pub fn plugin(app: &mut App) {
-    app.add_systems(
-        Update,
-        handle_mouse_move.run_if(in_state(GameState::Playing)),
-    );
+    app.add_plugins(Material2dPlugin::<EdgeMaterial>::default())
+        .init_resource::<EdgeHistory>()
+        .add_systems(
+            Update,
+            (
+                record_placed_edges,
+                (
+                    handle_mouse_move,
+                    undo.run_if(action_just_pressed(Action::Undo)),
+                    show_hint.run_if(action_just_pressed(Action::Hint)),
+                )
+                    .run_if(in_state(GameState::Playing)),
+                expire_hints,
+            ),
+        )
+        .add_systems(OnEnter(GameState::LevelExit), start_flow);
 }
 
 #[derive(Component)]
 pub struct Edge(pub Entity, pub Entity);
 
+/// Removes the edge it is triggered on.
+#[derive(Event)]
+pub struct RemoveEdge;
+
+/// Dashed edge that shows one missing edge of the solution for a moment.
+#[derive(Component)]
+struct Hint {
+    ends: (Entity, Entity),
+    timer: Timer,
+}
+
+/// Placed edges, from oldest to newest. May contain edges that were removed since.
+#[derive(Resource, Default)]
+pub struct EdgeHistory(Vec<Entity>);
+
+impl EdgeHistory {
+    /// Forgets all placed edges, when a level is generated.
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
 impl Edge {
     pub const WIDTH: f32 = 10.0;
-    pub const MAX_LEN: f32 = 400.0;
+    pub const MAX_LEN: f32 = spatial::MAX_EDGE_LEN;
+
+    /// Mesh, material and transform of an edge from `from` to `to`. This is how all edges are
+    /// spawned, including the ones between levels in the level select.
+    pub fn bundle(
+        from: Vec2,
+        to: Vec2,
+        z: f32,
+        material: EdgeMaterial,
+        meshes: &SharedMeshes,
+        materials: &mut Assets<EdgeMaterial>,
+    ) -> impl Bundle {
+        (
+            Mesh2d(meshes.quad.clone()),
+            MeshMaterial2d(materials.add(material)),
+            Self::transform(from, to, z),
+        )
+    }
+
+    /// Transform that stretches the shared unit quad from `from` to `to`.
+    fn transform(from: Vec2, to: Vec2, z: f32) -> Transform {
+        let diff = to - from;
+        Transform {
+            translation: ((from + to) / 2.0).extend(z),
+            rotation: Quat::from_rotation_z(diff.y.atan2(diff.x)),
+            scale: Vec3::new(diff.length(), Self::WIDTH, 1.0),
+        }
+    }
+
+    /// Moves and resizes an edge to go from `from` to `to`. The shader finds the length from the
+    /// transform, so the material doesn't change.
+    pub fn set_ends(from: Vec2, to: Vec2, transform: &mut Transform) {
+        *transform = Self::transform(from, to, transform.translation.z);
+    }
+}
+
+#[derive(AsBindGroup, Debug, Clone, Asset, TypePath)]
+pub struct EdgeMaterial {
+    /// 1 << 0: preview that follows the cursor, drawn dashed
+    /// 1 << 1: blocked by a vertex or too long, drawn dashed in the over-saturated color
+    /// 1 << 2: flowing, once the level is solved
+    #[uniform(0)]
+    pub bits: u32,
+    #[uniform(0)]
+    color: LinearRgba,
+    #[uniform(0)]
+    blocked: LinearRgba,
+}
+
+impl Material2d for EdgeMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/edge.wgsl".into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode2d {
+        AlphaMode2d::Blend
+    }
+}
+
+impl EdgeMaterial {
+    pub fn new(settings: &Settings) -> Self {
+        let mut material = Self {
+            bits: 0,
+            color: default(),
+            blocked: default(),
+        };
+        material.set_palette(&settings.theme.palette());
+        material
+    }
+
+    /// Material of the edge that follows the cursor while a vertex is selected.
+    pub fn preview(settings: &Settings) -> Self {
+        let mut material = Self::new(settings);
+        material.bits |= 1;
+        material
+    }
+
+    pub fn set_palette(&mut self, palette: &Palette) {
+        self.color = palette.foreground.into();
+        self.blocked = palette.over.into();
+    }
+
+    fn is_blocked(&self) -> bool {
+        self.bits & 2 != 0
+    }
+
+    fn set_blocked(&mut self, v: bool) {
+        if v {
+            self.bits |= 2;
+        } else {
+            self.bits &= !2;
+        }
+    }
+
+    fn set_flow(&mut self, v: bool) {
+        if v {
+            self.bits |= 4;
+        } else {
+            self.bits &= !4;
+        }
+    }
 }
 
 fn handle_mouse_move(
     mut cursor_evr: EventReader<CursorMoved>,
-    mut edge_q: Query<(&mut Transform, &Mesh2d), Without<Vertex>>,
+    mut edge_q: Query<(&mut Transform, &MeshMaterial2d<EdgeMaterial>), Without<Vertex>>,
     selected_q: Query<(&Selected, &Transform), With<Vertex>>,
     vertex_q: Query<&Transform, With<Vertex>>,
     cam_q: Query<(&Camera, &GlobalTransform)>,
-    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<EdgeMaterial>>,
+    grid: Res<VertexGrid>,
 ) {
     for ev in cursor_evr.read() {
         let Ok((selected, vertex_transform)) = selected_q.single() else {
             return;
         };
-        let Ok((mut transform, mesh2d)) = edge_q.get_mut(selected.edge) else {
+        let Ok((mut transform, material)) = edge_q.get_mut(selected.edge) else {
             return;
         };
         let Ok((cam, cam_transform)) = cam_q.single() else {
             return;
         };
         let vertex_pos = vertex_transform.translation.xy();
-        let Ok(pos) = cam
-            .viewport_to_world_2d(cam_transform, ev.position)
-            .map(|pos| get_obstacle_pos(vertex_pos, pos, vertex_q.iter()))
-        else {
+        let Ok(cursor_pos) = cam.viewport_to_world_2d(cam_transform, ev.position) else {
             return;
         };
-        let dist = vertex_pos.distance(pos).min(Edge::MAX_LEN + Vertex::RADIUS);
-        let Some(mesh) = meshes.get_mut(mesh2d) else {
-            return;
+        let obstacles = |target| {
+            grid.near_segment(vertex_pos, cursor_pos)
+                .filter(move |entity| Some(*entity) != target)
+                .filter_map(|entity| vertex_q.get(entity).ok())
+                .map(|transform| transform.translation.xy())
         };
-        *mesh = Rectangle::new(dist, Edge::WIDTH).into();
+        let pos = get_obstacle_pos(vertex_pos, cursor_pos, obstacles(None));
+        let end = vertex_pos + (pos - vertex_pos).clamp_length_max(Edge::MAX_LEN + Vertex::RADIUS);
+        Edge::set_ends(vertex_pos, end, &mut transform);
 
-        transform.translation = (vertex_pos
-            + (pos - vertex_pos).clamp_length_max(Edge::MAX_LEN + Vertex::RADIUS) / 2.0)
-            .extend(-1.0);
-        let diff = vertex_pos - pos;
-        transform.rotation = Quat::from_rotation_z(diff.y.atan2(diff.x));
+        // The vertex under the cursor is where the edge would go, so it doesn't block it.
+        let target = grid.near(cursor_pos, Vertex::RADIUS).find_map(|entity| {
+            let pos = vertex_q.get(entity).ok()?.translation.xy();
+            (pos.distance(cursor_pos) < Vertex::RADIUS).then_some((entity, pos))
+        });
+        let too_long = match target {
+            Some((_, pos)) => vertex_pos.distance(pos) > Edge::MAX_LEN + Vertex::RADIUS * 2.0,
+            None => vertex_pos.distance(cursor_pos) > Edge::MAX_LEN + Vertex::RADIUS,
+        };
+        let obstructed = get_obstacle_pos(
+            vertex_pos,
+            cursor_pos,
+            obstacles(target.map(|(entity, _)| entity)),
+        ) != cursor_pos;
+        // Only touch the material when it changes, so it isn't uploaded again every frame.
+        let blocked = too_long || obstructed;
+        if materials
+            .get(material)
+            .is_some_and(|material| material.is_blocked() != blocked)
+            && let Some(material) = materials.get_mut(material)
+        {
+            material.set_blocked(blocked);
+        }
+    }
+}
+
+/// Lets light flow along the edges of the solved level.
+fn start_flow(
+    edge_q: Query<&MeshMaterial2d<EdgeMaterial>, With<Edge>>,
+    mut materials: ResMut<Assets<EdgeMaterial>>,
+    settings: Res<Settings>,
+) {
+    if !effects::enabled(&settings) {
+        return;
+    }
+    for handle in &edge_q {
+        if let Some(material) = materials.get_mut(handle) {
+            material.set_flow(true);
+        }
+    }
+}
+
+fn record_placed_edges(
+    edge_q: Query<(Entity, &Edge), Added<Edge>>,
+    mut history: ResMut<EdgeHistory>,
+) {
+    for (entity, edge) in &edge_q {
+        // Edges to `Entity::PLACEHOLDER` follow the cursor and aren't placed yet.
+        if edge.1 != Entity::PLACEHOLDER {
+            history.0.push(entity);
+        }
+    }
+}
+
+/// Removes the most recently placed edge that still exists.
+fn undo(edge_q: Query<(), With<Edge>>, mut history: ResMut<EdgeHistory>, mut commands: Commands) {
+    while let Some(entity) = history.0.pop() {
+        if edge_q.contains(entity) {
+            commands.trigger_targets(RemoveEdge, entity);
+            return;
+        }
+    }
+}
+
+/// Shows an edge of the solution that isn't placed yet.
+fn show_hint(
+    vertex_q: Query<(&Vertex, &Transform)>,
+    hint_q: Query<Entity, With<Hint>>,
+    solution: Res<LevelSolution>,
+    settings: Res<Settings>,
+    meshes: Res<SharedMeshes>,
+    mut materials: ResMut<Assets<EdgeMaterial>>,
+    mut commands: Commands,
+) {
+    for entity in &hint_q {
+        commands.entity(entity).despawn();
+    }
+    let Some((ends, from, to)) = solution.0.iter().find_map(|(e1, e2)| {
+        let (vertex, from) = vertex_q.get(*e1).ok()?;
+        let (_, to) = vertex_q.get(*e2).ok()?;
+        (!vertex.edges.contains_key(e2)).then_some((
+            (*e1, *e2),
+            from.translation.xy(),
+            to.translation.xy(),
+        ))
+    }) else {
+        return;
+    };
+    commands.spawn((
+        Hint {
+            ends,
+            timer: Timer::from_seconds(3.0, TimerMode::Once),
+        },
+        Edge::bundle(
+            from,
+            to,
+            -1.0,
+            EdgeMaterial::preview(&settings),
+            &meshes,
+            &mut materials,
+        ),
+    ));
+}
+
+/// Removes hints after a while, once their edge is placed or when their level is left.
+fn expire_hints(
+    mut hint_q: Query<(Entity, &mut Hint)>,
+    vertex_q: Query<&Vertex>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, mut hint) in &mut hint_q {
+        let (e1, e2) = hint.ends;
+        let placed = vertex_q
+            .get(e1)
+            .ok()
+            .is_none_or(|vertex| vertex.edges.contains_key(&e2));
+        if hint.timer.tick(time.delta()).finished() || placed {
+            commands.entity(entity).despawn();
+        }
     }
 }
 
-#[allow(clippy::too_many_arguments)]
 pub fn handle_edge_click(
     trigger: Trigger<Pointer<Click>>,
-    edge_q: Query<&Edge>,
+    state: Res<State<GameState>>,
+    mut commands: Commands,
+) {
+    if *state.get() == GameState::Playing {
+        commands.trigger_targets(RemoveEdge, trigger.target());
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn remove_edge(
+    trigger: Trigger<RemoveEdge>,
+    edge_q: Query<(&Edge, &Transform)>,
     mut vertex_q: Query<(&mut Vertex, &MeshMaterial2d<VertexMaterial>, &Children)>,
     mut text_color_q: Query<&mut TextColor>,
     mut materials: ResMut<Assets<VertexMaterial>>,
     mut commands: Commands,
     check_if_solved_system: Res<CheckIfSolvedSystem>,
-    state: Res<State<GameState>>,
+    mut stats: ResMut<LevelStats>,
+    sound_effects: SoundEffects,
 ) {
-    if *state.get() != GameState::Playing {
-        return;
-    }
-    let Ok(edge) = edge_q.get(trigger.target()) else {
+    let Ok((edge, edge_transform)) = edge_q.get(trigger.target()) else {
         return;
     };
+    // Removing an edge from an over-saturated vertex can solve it.
+    let mut became_solved = false;
     if let Ok((mut vertex, handle, children)) = vertex_q.get_mut(edge.0) {
         vertex.edges.remove(&edge.1);
         let Ok(mut text_color) = text_color_q.get_mut(children[0]) else {
             return;
         };
         if let Some(material) = materials.get_mut(handle) {
-            material.set_solved(vertex.edges.len() == vertex.required_edges, &mut text_color);
+            became_solved |=
+                material.set_edge_count(vertex.edges.len(), vertex.required_edges, &mut text_color);
         }
     }
     if let Ok((mut vertex, handle, children)) = vertex_q.get_mut(edge.1) {
@@ -95,34 +365,22 @@ pub fn handle_edge_click(
             return;
         };
         if let Some(material) = materials.get_mut(handle) {
-            material.set_solved(vertex.edges.len() == vertex.required_edges, &mut text_color);
+            became_solved |=
+                material.set_edge_count(vertex.edges.len(), vertex.required_edges, &mut text_color);
         }
     }
     commands.entity(trigger.target()).despawn();
+    let pos = edge_transform.translation.xy();
+    commands.spawn(sound_effects.remove(pos));
+    if became_solved {
+        commands.spawn(sound_effects.vertex_solved(pos));
+    }
+    stats.moves += 1;
     commands.run_system(check_if_solved_system.0);
 }
 
-pub fn get_obstacle_pos<'a>(
-    pos1: Vec2,
-    pos2: Vec2,
-    vertex_q: impl Iterator<Item = &'a Transform>,
-) -> Vec2 {
-    let Ok(dir) = Dir2::new(pos2 - pos1) else {
-        return pos2;
-    };
-    let ray = Ray2d::new(pos1 + (Vertex::RADIUS + 0.1) * dir, dir);
-    let dist = (pos1.distance(pos2) - Vertex::RADIUS - 0.1).min(Edge::MAX_LEN);
-    let ray_cast = RayCast2d::from_ray(ray, dist);
-    let mut obstacle_dist = None;
-    for transform in vertex_q {
-        let circle = BoundingCircle::new(transform.translation.xy(), Vertex::RADIUS);
-        if let Some(result) = ray_cast.circle_intersection_at(&circle)
-            && (obstacle_dist.is_none() || obstacle_dist.unwrap() > result) {
-                obstacle_dist = Some(result);
-            }
-    }
-    match obstacle_dist {
-        Some(dist) => ray.get_point(dist),
-        None => pos2,
-    }
+/// Returns the first point on the way from `pos1` to `pos2` where a vertex at one of the
+/// `obstacles` positions is in the way, or `pos2` if none is.
+pub fn get_obstacle_pos(pos1: Vec2, pos2: Vec2, obstacles: impl Iterator<Item = Vec2>) -> Vec2 {
+    first_obstacle_pos(pos1, pos2, Vertex::RADIUS, Edge::MAX_LEN, obstacles)
 }
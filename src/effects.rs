@@ -0,0 +1,275 @@
+//! Animations that celebrate solved vertices and levels.
+
+use std::{collections::VecDeque, f32::consts::PI};
+
+use bevy::{
+    platform::collections::{HashMap, HashSet},
+    prelude::*,
+};
+use rand::Rng;
+
+use crate::{
+    GameState, edge::Edge, meshes::SharedMeshes, settings::Settings, theme::ThemeColor,
+    transition::Easing, vertex::Vertex,
+};
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<RippleOrigin>()
+        .add_systems(Startup, setup)
+        .add_systems(
+            Update,
+            (
+                celebrate_solved_vertices.run_if(in_state(GameState::Playing)),
+                move_particles,
+                play_tweens,
+            )
+                .chain(),
+        )
+        .add_systems(OnEnter(GameState::LevelExit), ripple_solved_level);
+}
+
+/// Animates the scale of an entity, optionally after a delay.
+#[derive(Component, Clone, Copy)]
+pub struct Tween {
+    pub kind: TweenKind,
+    pub secs: f32,
+    pub delay: f32,
+    pub easing: Easing,
+    /// Despawn the entity when the tween is finished instead of only removing the tween.
+    pub despawn: bool,
+    elapsed: f32,
+    /// Scale of the entity when the tween started.
+    start: Option<Vec3>,
+}
+
+#[derive(Clone, Copy)]
+pub enum TweenKind {
+    /// Scales from `from` to `to`.
+    Scale { from: Vec3, to: Vec3 },
+    /// Multiplies the scale by up to `peak` and goes back to the starting scale.
+    Pulse { peak: Vec3 },
+}
+
+impl Tween {
+    pub fn new(kind: TweenKind, secs: f32) -> Self {
+        Self {
+            kind,
+            secs,
+            delay: 0.0,
+            easing: Easing::Linear,
+            despawn: false,
+            elapsed: 0.0,
+            start: None,
+        }
+    }
+
+    pub fn with_delay(self, delay: f32) -> Self {
+        Self { delay, ..self }
+    }
+
+    pub fn with_easing(self, easing: Easing) -> Self {
+        Self { easing, ..self }
+    }
+
+    pub fn despawn_when_finished(self) -> Self {
+        Self {
+            despawn: true,
+            ..self
+        }
+    }
+
+    /// Returns the scale at time `t` from 0 to 1, given the scale `start` at time 0.
+    fn sample(&self, t: f32, start: Vec3) -> Vec3 {
+        let progress = self.easing.ease(t);
+        match self.kind {
+            TweenKind::Scale { from, to } => from.lerp(to, progress),
+            TweenKind::Pulse { peak } => start * Vec3::ONE.lerp(peak, (progress * PI).sin()),
+        }
+    }
+}
+
+/// Moves a particle and slows it down over time.
+#[derive(Component)]
+struct Particle {
+    velocity: Vec2,
+}
+
+impl Particle {
+    const RADIUS: f32 = 6.0;
+    const COUNT: usize = 14;
+    /// Fraction of the velocity that is kept per second.
+    const DRAG: f32 = 0.05;
+    const SCALE: Vec3 = Vec3::new(Self::RADIUS, Self::RADIUS, 1.0);
+}
+
+#[derive(Resource)]
+struct ParticleMaterial(Handle<ColorMaterial>);
+
+/// Vertex that the level was solved at, where the ripple starts.
+#[derive(Resource, Default)]
+struct RippleOrigin(Option<Entity>);
+
+/// Whether solved states are animated, which also respects the reduced motion setting.
+pub fn enabled(settings: &Settings) -> bool {
+    settings.effects && !settings.reduced_motion
+}
+
+fn setup(mut commands: Commands, mut materials: ResMut<Assets<ColorMaterial>>) {
+    commands.insert_resource(ParticleMaterial(materials.add(ColorMaterial::default())));
+}
+
+/// Pulses newly solved vertices and bursts particles out of them.
+fn celebrate_solved_vertices(
+    vertex_q: Query<(Entity, &Vertex, &Transform, Has<Tween>)>,
+    mut solved: Local<HashSet<Entity>>,
+    mut origin: ResMut<RippleOrigin>,
+    meshes: Res<SharedMeshes>,
+    particle_material: Res<ParticleMaterial>,
+    settings: Res<Settings>,
+    mut commands: Commands,
+) {
+    solved.retain(|entity| vertex_q.contains(*entity));
+    let mut rng = rand::thread_rng();
+    for (entity, vertex, transform, pulsing) in &vertex_q {
+        if vertex.edges.len() != vertex.required_edges {
+            solved.remove(&entity);
+            continue;
+        }
+        if !solved.insert(entity) || vertex.edges.is_empty() {
+            continue;
+        }
+        origin.0 = Some(entity);
+        // Let a running pulse finish, so it doesn't start from an enlarged scale.
+        if !enabled(&settings) || pulsing {
+            continue;
+        }
+        commands.entity(entity).insert(
+            Tween::new(
+                TweenKind::Pulse {
+                    peak: Vec3::splat(1.25),
+                },
+                0.3,
+            )
+            .with_easing(Easing::QuadraticOut),
+        );
+        let pos = transform.translation.xy();
+        for _ in 0..Particle::COUNT {
+            let dir = Vec2::from_angle(rng.gen_range(-PI..PI));
+            let speed = rng.gen_range(300.0..700.0);
+            commands.spawn((
+                Particle {
+                    velocity: dir * speed,
+                },
+                Mesh2d(meshes.circle.clone()),
+                MeshMaterial2d(particle_material.0.clone()),
+                ThemeColor::Foreground,
+                Transform::from_translation((pos + dir * Vertex::RADIUS).extend(-0.5))
+                    .with_scale(Particle::SCALE),
+                Tween::new(
+                    TweenKind::Scale {
+                        from: Particle::SCALE,
+                        to: Vec3::Z,
+                    },
+                    rng.gen_range(0.4..0.8),
+                )
+                .with_easing(Easing::QuadraticIn)
+                .despawn_when_finished(),
+            ));
+        }
+    }
+}
+
+/// Sends a pulse along the edges of the solved level, starting at the vertex that solved it.
+fn ripple_solved_level(
+    vertex_q: Query<&Vertex>,
+    edge_q: Query<(Entity, &Edge)>,
+    tween_q: Query<(), With<Tween>>,
+    origin: Res<RippleOrigin>,
+    settings: Res<Settings>,
+    mut commands: Commands,
+) {
+    const SECS_PER_HOP: f32 = 0.08;
+    if !enabled(&settings) {
+        return;
+    }
+    let Some(origin) = origin.0 else {
+        return;
+    };
+    // Breadth-first search for the number of edges between each vertex and the origin.
+    let mut hops = HashMap::<Entity, u32>::from_iter([(origin, 0)]);
+    let mut queue = VecDeque::from([origin]);
+    while let Some(entity) = queue.pop_front() {
+        let Ok(vertex) = vertex_q.get(entity) else {
+            continue;
+        };
+        let hop = hops[&entity];
+        for neighbor in vertex.edges.keys() {
+            if !hops.contains_key(neighbor) {
+                hops.insert(*neighbor, hop + 1);
+                queue.push_back(*neighbor);
+            }
+        }
+    }
+    for (entity, hop) in &hops {
+        // Vertices that are still pulsing from being solved sit this one out.
+        if tween_q.contains(*entity) {
+            continue;
+        }
+        commands.entity(*entity).insert(
+            Tween::new(
+                TweenKind::Pulse {
+                    peak: Vec3::splat(1.15),
+                },
+                0.3,
+            )
+            .with_delay(*hop as f32 * SECS_PER_HOP),
+        );
+    }
+    for (entity, edge) in &edge_q {
+        // Edges of other components than the origin's aren't reached by the ripple.
+        let (Some(hop0), Some(hop1)) = (hops.get(&edge.0), hops.get(&edge.1)) else {
+            continue;
+        };
+        let delay = (*hop0 as f32 + *hop1 as f32) / 2.0 * SECS_PER_HOP;
+        commands.entity(entity).insert(
+            Tween::new(
+                TweenKind::Pulse {
+                    peak: Vec3::new(1.0, 2.0, 1.0),
+                },
+                0.3,
+            )
+            .with_delay(delay),
+        );
+    }
+}
+
+fn move_particles(mut particle_q: Query<(&mut Particle, &mut Transform)>, time: Res<Time>) {
+    let dt = time.delta_secs();
+    for (mut particle, mut transform) in &mut particle_q {
+        transform.translation += (particle.velocity * dt).extend(0.0);
+        particle.velocity *= Particle::DRAG.powf(dt);
+    }
+}
+
+fn play_tweens(
+    mut tween_q: Query<(Entity, &mut Tween, &mut Transform)>,
+    time: Res<Time>,
+    mut commands: Commands,
+) {
+    for (entity, mut tween, mut transform) in &mut tween_q {
+        tween.elapsed += time.delta_secs();
+        let t = (tween.elapsed - tween.delay) / tween.secs;
+        if t < 0.0 {
+            continue;
+        }
+        let start = *tween.start.get_or_insert(transform.scale);
+        transform.scale = tween.sample(t.min(1.0), start);
+        if t >= 1.0 {
+            if tween.despawn {
+                commands.entity(entity).try_despawn();
+            } else {
+                commands.entity(entity).try_remove::<Tween>();
+            }
+        }
+    }
+}
@@ -0,0 +1,258 @@
+//! Writes puzzles as SVG drawings and takes screenshots, for sharing and printing.
+
+use std::{fmt::Write, fs, path::Path};
+
+use bevy::{
+    platform::collections::HashMap,
+    prelude::*,
+    render::view::screenshot::{Screenshot, save_to_disk},
+};
+
+use crate::{
+    GameState,
+    edge::Edge,
+    input::{Action, action_just_pressed},
+    level::{Level, LevelSolution, Puzzle},
+    locale::Locale,
+    vertex::Vertex,
+};
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        export_level.run_if(in_state(GameState::Playing).and(action_just_pressed(Action::Export))),
+    );
+}
+
+/// Directory that in-game exports are written to.
+const DIR: &str = "exports";
+
+/// A puzzle as it is drawn in exports: numbered vertices and some of the edges between them.
+pub struct PuzzleDrawing {
+    pub title: String,
+    /// Position and number of each vertex.
+    pub vertices: Vec<(Vec2, usize)>,
+    /// Edges as indices into `vertices`.
+    pub edges: Vec<(usize, usize)>,
+}
+
+impl PuzzleDrawing {
+    /// Space around the vertices, which also fits the title.
+    const MARGIN: f32 = 120.0;
+
+    /// Drawing of `puzzle` with its solution or without any edges.
+    pub fn from_puzzle(puzzle: &Puzzle, title: String, solution: bool) -> Self {
+        Self {
+            title,
+            vertices: puzzle.vertices.clone(),
+            edges: if solution {
+                puzzle.solution.clone()
+            } else {
+                Vec::new()
+            },
+        }
+    }
+
+    /// Area covered by the drawing, in world units with y pointing up.
+    pub fn bounds(&self) -> Rect {
+        self.vertices
+            .iter()
+            .map(|(pos, _)| {
+                Rect::from_center_half_size(*pos, Vec2::splat(Vertex::RADIUS + Self::MARGIN))
+            })
+            .reduce(|a, b| a.union(b))
+            .unwrap_or_default()
+    }
+
+    /// SVG elements of the drawing, with y flipped to point down as in SVG.
+    pub fn svg_elements(&self) -> String {
+        let bounds = self.bounds();
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<text x="{}" y="{}" font-family="sans-serif" font-size="50" text-anchor="middle">{}</text>"#,
+            bounds.center().x,
+            -bounds.max.y + 60.0,
+            escape(&self.title),
+        );
+        for (i1, i2) in &self.edges {
+            let (from, to) = (self.vertices[*i1].0, self.vertices[*i2].0);
+            let _ = writeln!(
+                svg,
+                r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="black" stroke-width="{}"/>"#,
+                from.x,
+                -from.y,
+                to.x,
+                -to.y,
+                Edge::WIDTH,
+            );
+        }
+        for (pos, number) in &self.vertices {
+            let _ = writeln!(
+                svg,
+                r#"<circle cx="{}" cy="{}" r="{}" fill="white" stroke="black" stroke-width="6"/>"#,
+                pos.x,
+                -pos.y,
+                Vertex::RADIUS,
+            );
+            let _ = writeln!(
+                svg,
+                r#"<text x="{}" y="{}" font-family="sans-serif" font-size="60" text-anchor="middle" dominant-baseline="central">{number}</text>"#,
+                pos.x, -pos.y,
+            );
+        }
+        svg
+    }
+
+    pub fn to_svg(&self) -> String {
+        let bounds = self.bounds();
+        let size = bounds.size();
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\" width=\"{}\" height=\"{}\">\n\
+            <rect x=\"{0}\" y=\"{1}\" width=\"{2}\" height=\"{3}\" fill=\"white\"/>\n\
+            {}</svg>\n",
+            bounds.min.x,
+            -bounds.max.y,
+            size.x,
+            size.y,
+            size.x / 4.0,
+            size.y / 4.0,
+            self.svg_elements(),
+        )
+    }
+
+    /// The drawing scaled to fit into `rect` of an enclosing SVG, keeping its aspect ratio.
+    pub fn to_nested_svg(&self, rect: Rect) -> String {
+        let bounds = self.bounds();
+        let size = bounds.size();
+        format!(
+            "<svg x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" viewBox=\"{} {} {} {}\">\n{}</svg>\n",
+            rect.min.x,
+            rect.min.y,
+            rect.width(),
+            rect.height(),
+            bounds.min.x,
+            -bounds.max.y,
+            size.x,
+            size.y,
+            self.svg_elements(),
+        )
+    }
+}
+
+/// Escapes text for use in SVG.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Name of exported files of `level`, whose number is also the seed it is generated from.
+pub fn file_stem(level: u64) -> String {
+    format!("level-{level}")
+}
+
+/// Title of the drawings of `level`, the same as the level text in the game.
+fn title(level: u64, locale: &Locale) -> String {
+    locale.format("level", &[("level", &level)])
+}
+
+/// Writes `level` to `path` as SVG, with its solution if `solution` is set. Used by the
+/// `--export-svg` and `--export-solution-svg` command line options.
+pub fn export_svg(level: u64, path: &Path, solution: bool) -> AppExit {
+    let title = title(level, &Locale::default());
+    let drawing = PuzzleDrawing::from_puzzle(&Puzzle::generate(level), title, solution);
+    match fs::write(path, drawing.to_svg()) {
+        Ok(()) => AppExit::Success,
+        Err(e) => {
+            eprintln!("Failed to write {}: {e}", path.display());
+            AppExit::error()
+        }
+    }
+}
+
+/// Writes the current level with the player's edges, its solution and a screenshot to
+/// [`DIR`].
+fn export_level(
+    vertex_q: Query<(Entity, &Vertex, &Transform)>,
+    edge_q: Query<&Edge>,
+    solution: Res<LevelSolution>,
+    level: Res<Level>,
+    locale: Res<Locale>,
+    mut commands: Commands,
+) {
+    if let Err(e) = fs::create_dir_all(DIR) {
+        warn!("Failed to create {DIR}: {e}");
+        return;
+    }
+    let stem = format!("{DIR}/{}", file_stem(level.0));
+    let title = title(level.0, &locale);
+
+    let indices = vertex_q
+        .iter()
+        .enumerate()
+        .map(|(i, (entity, ..))| (entity, i))
+        .collect::<HashMap<_, _>>();
+    let edges = |pairs: &mut dyn Iterator<Item = (Entity, Entity)>| {
+        pairs
+            .filter_map(|(e1, e2)| Some((*indices.get(&e1)?, *indices.get(&e2)?)))
+            .collect()
+    };
+    let vertices = vertex_q
+        .iter()
+        .map(|(_, vertex, transform)| (transform.translation.xy(), vertex.required_edges))
+        .collect::<Vec<_>>();
+    let drawing = PuzzleDrawing {
+        title: title.clone(),
+        vertices: vertices.clone(),
+        edges: edges(&mut edge_q.iter().map(|edge| (edge.0, edge.1))),
+    };
+    let solution = PuzzleDrawing {
+        title,
+        vertices,
+        edges: edges(&mut solution.0.iter().copied()),
+    };
+    for (path, drawing) in [
+        (format!("{stem}.svg"), drawing),
+        (format!("{stem}-solution.svg"), solution),
+    ] {
+        match fs::write(&path, drawing.to_svg()) {
+            Ok(()) => info!("Exported {path}"),
+            Err(e) => warn!("Failed to write {path}: {e}"),
+        }
+    }
+
+    commands
+        .spawn(Screenshot::primary_window())
+        .observe(save_to_disk(format!("{stem}.png")));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn svg_flips_y_and_fits_view_box() {
+        let drawing = PuzzleDrawing {
+            title: "Tom & <Jerry>".to_string(),
+            vertices: vec![(Vec2::new(0.0, 0.0), 1), (Vec2::new(300.0, 200.0), 1)],
+            edges: vec![(0, 1)],
+        };
+        let svg = drawing.to_svg();
+
+        let bounds = drawing.bounds();
+        let half_size = Vertex::RADIUS + PuzzleDrawing::MARGIN;
+        assert_eq!(bounds.min, Vec2::splat(-half_size));
+        assert_eq!(bounds.max, Vec2::new(300.0, 200.0) + half_size);
+        let size = bounds.size();
+        assert!(svg.contains(&format!(
+            r#"viewBox="{} {} {} {}""#,
+            bounds.min.x, -bounds.max.y, size.x, size.y,
+        )));
+
+        assert!(svg.contains(r#"<line x1="0" y1="-0" x2="300" y2="-200""#));
+        assert!(svg.contains(r#"<circle cx="300" cy="-200""#));
+        assert!(svg.contains("Tom &amp; &lt;Jerry&gt;"));
+        assert!(!svg.contains("<Jerry>"));
+    }
+}
@@ -0,0 +1,264 @@
+use bevy::{
+    core_pipeline::bloom::Bloom,
+    prelude::*,
+    window::{Monitor, PresentMode, PrimaryWindow, VideoModeSelection, WindowMode},
+};
+
+use crate::settings::Settings;
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (
+            apply_window,
+            apply_camera.run_if(resource_changed::<Settings>),
+        ),
+    );
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScreenMode {
+    #[default]
+    Windowed,
+    Borderless,
+    /// Exclusive fullscreen, which can change the resolution of the monitor.
+    Exclusive,
+}
+
+impl ScreenMode {
+    pub const ALL: [Self; 3] = [Self::Windowed, Self::Borderless, Self::Exclusive];
+
+    /// Key of the mode's name in the string tables.
+    pub fn name_key(self) -> &'static str {
+        match self {
+            Self::Windowed => "screen-mode-windowed",
+            Self::Borderless => "screen-mode-borderless",
+            Self::Exclusive => "screen-mode-exclusive",
+        }
+    }
+
+    /// Name used in the settings file.
+    pub fn key(self) -> &'static str {
+        match self {
+            Self::Windowed => "windowed",
+            Self::Borderless => "borderless",
+            Self::Exclusive => "exclusive",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|mode| mode.key() == key)
+    }
+
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|mode| *mode == self).unwrap();
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum BloomPreset {
+    Off,
+    Low,
+    #[default]
+    Natural,
+    High,
+}
+
+impl BloomPreset {
+    pub const ALL: [Self; 4] = [Self::Off, Self::Low, Self::Natural, Self::High];
+
+    /// Key of the preset's name in the string tables.
+    pub fn name_key(self) -> &'static str {
+        match self {
+            Self::Off => "bloom-off",
+            Self::Low => "bloom-low",
+            Self::Natural => "bloom-natural",
+            Self::High => "bloom-high",
+        }
+    }
+
+    /// Name used in the settings file.
+    pub fn key(self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Low => "low",
+            Self::Natural => "natural",
+            Self::High => "high",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|preset| preset.key() == key)
+    }
+
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|preset| *preset == self).unwrap();
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    fn bloom(self) -> Option<Bloom> {
+        let intensity = match self {
+            Self::Off => return None,
+            Self::Low => 0.08,
+            Self::Natural => Bloom::NATURAL.intensity,
+            Self::High => 0.3,
+        };
+        Some(Bloom {
+            intensity,
+            ..Bloom::NATURAL
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct GraphicsSettings {
+    pub screen_mode: ScreenMode,
+    /// Index of the fullscreen monitor, counted from the left.
+    pub monitor: usize,
+    /// Resolution in exclusive fullscreen, or the current resolution of the monitor if `None`.
+    pub resolution: Option<UVec2>,
+    pub vsync: bool,
+    pub msaa: bool,
+    pub bloom: BloomPreset,
+}
+
+impl Default for GraphicsSettings {
+    fn default() -> Self {
+        Self {
+            screen_mode: default(),
+            monitor: 0,
+            resolution: None,
+            vsync: true,
+            msaa: true,
+            bloom: default(),
+        }
+    }
+}
+
+impl GraphicsSettings {
+    /// Resolutions that `monitor` supports, from largest to smallest.
+    pub fn resolutions(monitor: &Monitor) -> Vec<UVec2> {
+        let mut sizes = monitor
+            .video_modes
+            .iter()
+            .map(|mode| mode.physical_size)
+            .collect::<Vec<_>>();
+        sizes.sort_by_key(|size| std::cmp::Reverse((size.x, size.y)));
+        sizes.dedup();
+        sizes
+    }
+
+    /// Switches to the next resolution of `monitor`, going back to the current resolution of
+    /// the monitor after the smallest one.
+    pub fn next_resolution(&mut self, monitor: Option<&Monitor>) {
+        let sizes = monitor.map(Self::resolutions).unwrap_or_default();
+        let i = self
+            .resolution
+            .and_then(|resolution| sizes.iter().position(|size| *size == resolution));
+        self.resolution = match i {
+            None => sizes.first().copied(),
+            Some(i) => sizes.get(i + 1).copied(),
+        };
+    }
+
+    pub fn resolution_key(&self) -> String {
+        match self.resolution {
+            Some(size) => format!("{}x{}", size.x, size.y),
+            None => "native".to_string(),
+        }
+    }
+
+    pub fn set_resolution_key(&mut self, key: &str) -> bool {
+        if key == "native" {
+            self.resolution = None;
+            return true;
+        }
+        let Some((Ok(x), Ok(y))) = key.split_once('x').map(|(x, y)| (x.parse(), y.parse())) else {
+            return false;
+        };
+        self.resolution = Some(UVec2::new(x, y));
+        true
+    }
+}
+
+/// Monitors from left to right, which is the order [`GraphicsSettings::monitor`] counts in.
+pub fn sorted_monitors<'a>(monitor_q: &'a Query<(Entity, &Monitor)>) -> Vec<(Entity, &'a Monitor)> {
+    let mut monitors = monitor_q.iter().collect::<Vec<_>>();
+    monitors.sort_by_key(|(_, monitor)| monitor.physical_position.x);
+    monitors
+}
+
+/// Applies the screen mode and vsync settings, also when monitors are connected.
+fn apply_window(
+    settings: Res<Settings>,
+    new_monitor_q: Query<(), Added<Monitor>>,
+    monitor_q: Query<(Entity, &Monitor)>,
+    mut window: Single<&mut Window, With<PrimaryWindow>>,
+) {
+    if !settings.is_changed() && new_monitor_q.is_empty() {
+        return;
+    }
+    let graphics = &settings.graphics;
+    let monitors = sorted_monitors(&monitor_q);
+    let monitor = monitors.get(graphics.monitor);
+    let selection = match monitor {
+        Some((entity, _)) => MonitorSelection::Entity(*entity),
+        None => MonitorSelection::Current,
+    };
+    let mode = match graphics.screen_mode {
+        ScreenMode::Windowed => WindowMode::Windowed,
+        ScreenMode::Borderless => WindowMode::BorderlessFullscreen(selection),
+        ScreenMode::Exclusive => {
+            // Use the highest refresh rate at the chosen resolution.
+            let video_mode = monitor
+                .zip(graphics.resolution)
+                .and_then(|((_, monitor), size)| {
+                    monitor
+                        .video_modes
+                        .iter()
+                        .filter(|mode| mode.physical_size == size)
+                        .max_by_key(|mode| mode.refresh_rate_millihertz)
+                });
+            WindowMode::Fullscreen(
+                selection,
+                video_mode.map_or(VideoModeSelection::Current, |mode| {
+                    VideoModeSelection::Specific(*mode)
+                }),
+            )
+        }
+    };
+    if window.mode != mode {
+        window.mode = mode;
+    }
+    let present_mode = if graphics.vsync {
+        PresentMode::AutoVsync
+    } else {
+        PresentMode::AutoNoVsync
+    };
+    if window.present_mode != present_mode {
+        window.present_mode = present_mode;
+    }
+}
+
+/// Applies the bloom and MSAA settings.
+fn apply_camera(
+    settings: Res<Settings>,
+    camera: Single<(Entity, &Msaa), With<Camera2d>>,
+    mut commands: Commands,
+) {
+    let (entity, msaa) = *camera;
+    let graphics = &settings.graphics;
+    match graphics.bloom.bloom() {
+        Some(bloom) => commands.entity(entity).insert(bloom),
+        None => commands.entity(entity).remove::<Bloom>(),
+    };
+    let new_msaa = if graphics.msaa {
+        Msaa::Sample4
+    } else {
+        Msaa::Off
+    };
+    if *msaa != new_msaa {
+        commands.entity(entity).insert(new_msaa);
+    }
+}
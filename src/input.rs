@@ -0,0 +1,263 @@
+//! Actions that can be bound to a keyboard key and a gamepad button.
+
+use bevy::{
+    ecs::system::SystemParam,
+    input::InputSystem,
+    platform::collections::HashMap,
+    prelude::*,
+    reflect::{DynamicEnum, DynamicVariant, Enum, TypeInfo, Typed, VariantInfo},
+};
+
+use crate::settings::Settings;
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<Rebinding>()
+        .add_systems(PreUpdate, capture_binding.after(InputSystem));
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Pause,
+    Undo,
+    Hint,
+    Restart,
+    PagePrev,
+    PageNext,
+    Export,
+}
+
+impl Action {
+    pub const ALL: [Self; 7] = [
+        Self::Pause,
+        Self::Undo,
+        Self::Hint,
+        Self::Restart,
+        Self::PagePrev,
+        Self::PageNext,
+        Self::Export,
+    ];
+
+    /// Name used in the settings file.
+    fn key(self) -> &'static str {
+        match self {
+            Self::Pause => "pause",
+            Self::Undo => "undo",
+            Self::Hint => "hint",
+            Self::Restart => "restart",
+            Self::PagePrev => "page_prev",
+            Self::PageNext => "page_next",
+            Self::Export => "export",
+        }
+    }
+
+    /// Key of the action's name in the string tables.
+    pub fn name_key(self) -> &'static str {
+        match self {
+            Self::Pause => "action-pause",
+            Self::Undo => "action-undo",
+            Self::Hint => "action-hint",
+            Self::Restart => "action-restart",
+            Self::PagePrev => "action-page-prev",
+            Self::PageNext => "action-page-next",
+            Self::Export => "action-export",
+        }
+    }
+
+    fn default_binding(self) -> Binding {
+        let (key, button) = match self {
+            Self::Pause => (KeyCode::Escape, GamepadButton::Start),
+            Self::Undo => (KeyCode::KeyZ, GamepadButton::West),
+            Self::Hint => (KeyCode::KeyH, GamepadButton::North),
+            Self::Restart => (KeyCode::KeyR, GamepadButton::Select),
+            Self::PagePrev => (KeyCode::ArrowLeft, GamepadButton::DPadLeft),
+            Self::PageNext => (KeyCode::ArrowRight, GamepadButton::DPadRight),
+            Self::Export => (KeyCode::F12, GamepadButton::Mode),
+        };
+        Binding { key, button }
+    }
+}
+
+/// Keyboard key and gamepad button that trigger an action.
+#[derive(Clone, Copy)]
+pub struct Binding {
+    pub key: KeyCode,
+    pub button: GamepadButton,
+}
+
+/// Which half of a [`Binding`] is meant.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum BindingSlot {
+    Key,
+    Button,
+}
+
+#[derive(Clone)]
+pub struct Bindings(HashMap<Action, Binding>);
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Self(
+            Action::ALL
+                .into_iter()
+                .map(|action| (action, action.default_binding()))
+                .collect(),
+        )
+    }
+}
+
+impl Bindings {
+    pub fn get(&self, action: Action) -> Binding {
+        self.0[&action]
+    }
+
+    /// Binds `action` to the key or button of `new`, depending on `slot`. An action that was
+    /// bound to it before gets the old binding of `action` instead.
+    fn rebind(&mut self, action: Action, slot: BindingSlot, new: Binding) {
+        let old = self.get(action);
+        for (other, binding) in &mut self.0 {
+            match slot {
+                BindingSlot::Key if binding.key == new.key => binding.key = old.key,
+                BindingSlot::Button if binding.button == new.button => {
+                    binding.button = old.button;
+                }
+                _ => {}
+            }
+            if *other == action {
+                match slot {
+                    BindingSlot::Key => binding.key = new.key,
+                    BindingSlot::Button => binding.button = new.button,
+                }
+            }
+        }
+    }
+
+    /// Sets the binding `key` from its saved `value`, such as `key_pause=Escape`. Returns
+    /// whether the binding was valid.
+    pub fn set(&mut self, key: &str, value: &str) -> bool {
+        let Some((slot, action)) = key.split_once('_') else {
+            return false;
+        };
+        let Some(binding) = Action::ALL
+            .into_iter()
+            .find(|a| a.key() == action)
+            .and_then(|action| self.0.get_mut(&action))
+        else {
+            return false;
+        };
+        match slot {
+            "key" => from_variant_name(value).map(|key| binding.key = key),
+            "button" => from_variant_name(value).map(|button| binding.button = button),
+            _ => None,
+        }
+        .is_some()
+    }
+
+    pub fn to_file_contents(&self) -> String {
+        let mut contents = String::new();
+        for action in Action::ALL {
+            let binding = self.get(action);
+            contents += &format!(
+                "key_{0}={1}\nbutton_{0}={2}\n",
+                action.key(),
+                binding.key.variant_name(),
+                binding.button.variant_name(),
+            );
+        }
+        contents
+    }
+}
+
+/// Parses a unit variant of an enum such as [`KeyCode`] from its name.
+fn from_variant_name<T: FromReflect + Typed>(name: &str) -> Option<T> {
+    // `from_reflect` panics on names that aren't variants.
+    let TypeInfo::Enum(info) = T::type_info() else {
+        return None;
+    };
+    if !matches!(info.variant(name), Some(VariantInfo::Unit(_))) {
+        return None;
+    }
+    T::from_reflect(&DynamicEnum::new(name.to_string(), DynamicVariant::Unit))
+}
+
+/// Name of `key` as shown to the player.
+pub fn key_name(key: KeyCode) -> String {
+    let name = key.variant_name();
+    name.strip_prefix("Key")
+        .or_else(|| name.strip_prefix("Digit"))
+        .unwrap_or(name)
+        .to_string()
+}
+
+/// Name of `button` as shown to the player.
+pub fn button_name(button: GamepadButton) -> String {
+    button.variant_name().to_string()
+}
+
+/// The action and slot that the next pressed key or button is bound to.
+#[derive(Resource, Default)]
+pub struct Rebinding(pub Option<(Action, BindingSlot)>);
+
+/// Reads actions through the bindings in the settings.
+#[derive(SystemParam)]
+pub struct Actions<'w, 's> {
+    keyboard: Res<'w, ButtonInput<KeyCode>>,
+    gamepad_q: Query<'w, 's, &'static Gamepad>,
+    settings: Res<'w, Settings>,
+    rebinding: Res<'w, Rebinding>,
+}
+
+impl Actions<'_, '_> {
+    pub fn just_pressed(&self, action: Action) -> bool {
+        // The key that finishes rebinding shouldn't also trigger its action.
+        if self.rebinding.0.is_some() || self.rebinding.is_changed() {
+            return false;
+        }
+        let binding = self.settings.bindings.get(action);
+        self.keyboard.just_pressed(binding.key)
+            || self
+                .gamepad_q
+                .iter()
+                .any(|gamepad| gamepad.just_pressed(binding.button))
+    }
+}
+
+/// Run condition that is true when `action` was just pressed.
+pub fn action_just_pressed(action: Action) -> impl FnMut(Actions) -> bool + Clone {
+    move |actions: Actions| actions.just_pressed(action)
+}
+
+fn capture_binding(
+    mut rebinding: ResMut<Rebinding>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepad_q: Query<&Gamepad>,
+    mut settings: ResMut<Settings>,
+) {
+    let Some((action, slot)) = rebinding.0 else {
+        return;
+    };
+    // Escape cancels rebinding both keys and buttons.
+    if keyboard.just_pressed(KeyCode::Escape) {
+        rebinding.0 = None;
+        return;
+    }
+    let mut binding = settings.bindings.get(action);
+    match slot {
+        BindingSlot::Key => {
+            let Some(key) = keyboard.get_just_pressed().next() else {
+                return;
+            };
+            binding.key = *key;
+        }
+        BindingSlot::Button => {
+            let Some(button) = gamepad_q
+                .iter()
+                .find_map(|gamepad| gamepad.get_just_pressed().next())
+            else {
+                return;
+            };
+            binding.button = *button;
+        }
+    }
+    settings.bindings.rebind(action, slot, binding);
+    rebinding.0 = None;
+}
@@ -1,28 +1,45 @@
-use std::f32::consts::PI;
+use std::{f32::consts::PI, ops::RangeInclusive, time::Duration};
 
 use bevy::{
-    ecs::system::SystemId,
+    ecs::system::{SystemId, SystemParam},
     math::bounding::{BoundingCircle, RayCast2d},
     prelude::*,
+    time::Stopwatch,
 };
 use rand::{Rng, SeedableRng, rngs::StdRng};
 
 use crate::{
     GameState,
-    audio::BeatLevelAudioHandle,
-    edge::Edge,
+    audio::SoundEffects,
+    camera::CameraView,
+    edge::{Edge, EdgeHistory},
+    input::{Action, action_just_pressed},
+    locale::Locale,
+    meshes::SharedMeshes,
+    progress::{LevelResult, Progress, format_time},
+    settings::Settings,
+    spatial::SpatialGrid,
+    theme::ThemeColor,
+    transition::{FadeOverlay, Transition},
     vertex::{Vertex, VertexMaterial},
 };
 
 pub fn plugin(app: &mut App) {
     app.insert_resource(Level(1))
         .init_resource::<NextLevelTimer>()
+        .init_resource::<LevelStats>()
+        .init_resource::<LevelSolution>()
         .add_systems(Startup, (setup, generate_level))
         .add_systems(
             Update,
             (
                 enter_level.run_if(in_state(GameState::LevelEnter)),
                 exit_level.run_if(in_state(GameState::LevelExit)),
+                tick_level_stats.run_if(in_state(GameState::Playing)),
+                restart_level
+                    .run_if(in_state(GameState::Playing).and(action_just_pressed(Action::Restart))),
+                update_level_stats_text,
+                update_level_text.run_if(resource_changed::<Level>.or(resource_changed::<Locale>)),
             ),
         )
         .add_systems(
@@ -40,101 +57,274 @@ pub struct Level(pub u64);
 #[derive(Component)]
 pub struct LevelText;
 
+/// Performance in the current level.
+#[derive(Resource, Default)]
+pub struct LevelStats {
+    pub time: Stopwatch,
+    /// Number of edge placements and removals.
+    pub moves: u32,
+    /// Number of edges in the solution.
+    min_moves: u32,
+}
+
+impl LevelStats {
+    /// Time per solution edge allowed for the time star.
+    const PAR_TIME_PER_EDGE: Duration = Duration::from_secs(4);
+
+    /// Rates the run from 1 to 3 stars, with one star each for using no extra moves and
+    /// for solving the level within the par time.
+    fn stars(&self) -> u8 {
+        let par_time = Self::PAR_TIME_PER_EDGE * self.min_moves;
+        1 + (self.moves <= self.min_moves) as u8 + (self.time.elapsed() <= par_time) as u8
+    }
+}
+
+#[derive(Component)]
+struct LevelStatsText;
+
+/// Edges of the generated solution between the vertices of the current level.
+#[derive(Resource, Default)]
+pub struct LevelSolution(pub Vec<(Entity, Entity)>);
+
+/// Rules that can vary from level to level.
+#[derive(Resource)]
+pub struct LevelRules {
+    /// Whether edges may be added to a vertex that already has all of its required edges.
+    pub allow_over_saturation: bool,
+    /// Whether vertices show how many of their required edges are placed.
+    pub progress_rings: bool,
+}
+
+impl LevelRules {
+    fn for_level(level: u64) -> Self {
+        Self {
+            // Keep the first levels forgiving.
+            allow_over_saturation: level > 5,
+            // Late levels leave counting the edges to the player.
+            progress_rings: level < 50,
+        }
+    }
+}
+
 fn setup(mut commands: Commands) {
     let id = commands.register_system(check_if_solved);
     commands.insert_resource(CheckIfSolvedSystem(id));
 
     commands.spawn((
         LevelText,
-        Text2d::new("Level 1"),
+        Text2d::default(),
         TextFont {
             font_size: 60.0,
             ..default()
         },
+        ThemeColor::Foreground,
         Transform::from_xyz(0.0, 690.0, -2.0),
+        children![(
+            LevelStatsText,
+            Text2d::default(),
+            TextFont {
+                font_size: 40.0,
+                ..default()
+            },
+            ThemeColor::Foreground,
+            Transform::from_xyz(500.0, 0.0, 0.0),
+        )],
     ));
 }
 
+/// Vertices and solution of a level, before any entities are spawned.
+#[derive(PartialEq, Debug)]
+pub struct Puzzle {
+    /// Position and number of required edges of each vertex.
+    pub vertices: Vec<(Vec2, usize)>,
+    /// Edges of the generated solution, as indices into `vertices`.
+    pub solution: Vec<(usize, usize)>,
+}
+
+impl Puzzle {
+    /// Generates the puzzle of `level`, which is also the seed of its random numbers.
+    pub fn generate(level: u64) -> Self {
+        Self::generate_seeded(level, level)
+    }
+
+    /// Generates a puzzle as big and dense as the ones of `level`, from another `seed`.
+    pub fn generate_seeded(level: u64, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        let (positions, vertex_count, _) = place_vertices(level, &mut rng);
+
+        let mut required_edges = vec![0; vertex_count];
+        let mut solution = Vec::new();
+        let edge_probability = edge_probability(level);
+
+        let mut grid = SpatialGrid::new(Vertex::GRID_CELL_SIZE);
+        for (i, pos) in positions.iter().enumerate() {
+            grid.insert(*pos, i);
+        }
+        const MAX_DIST: f32 = Edge::MAX_LEN + Vertex::RADIUS * 2.0;
+        for (i1, pos1) in positions.iter().enumerate() {
+            // Later vertices in range, in the same order as a scan over all of them so that
+            // levels don't change.
+            let mut candidates = grid
+                .near_segment(*pos1, *pos1, MAX_DIST)
+                .map(|(_, i2)| i2)
+                .filter(|i2| (i1 + 1..vertex_count).contains(i2))
+                .collect::<Vec<_>>();
+            candidates.sort_unstable();
+            for i2 in candidates {
+                let pos2 = positions[i2];
+                let dist = pos1.distance(pos2);
+                if dist < MAX_DIST
+                    && (required_edges[i1] == 0 || rng.r#gen::<f32>() < edge_probability)
+                    && {
+                        let dir = Dir2::new(pos2 - pos1).unwrap_or(Dir2::X);
+                        let ray_cast = RayCast2d::new(
+                            *pos1 + (Vertex::RADIUS + 0.1) * dir,
+                            dir,
+                            dist - 2.0 * (Vertex::RADIUS + 0.1),
+                        );
+                        grid.near_segment(*pos1, pos2, Vertex::RADIUS)
+                            .all(|(obstacle_pos, _)| {
+                                let circle = BoundingCircle::new(obstacle_pos, Vertex::RADIUS);
+                                ray_cast.circle_intersection_at(&circle).is_none()
+                            })
+                    }
+                {
+                    required_edges[i1] += 1;
+                    required_edges[i2] += 1;
+                    solution.push((i1, i2));
+                }
+            }
+        }
+
+        // Vertices without edges are left out, which shifts the indices of later ones.
+        let mut indices = Vec::with_capacity(vertex_count);
+        let mut vertices = Vec::new();
+        for (pos, required) in positions.into_iter().zip(required_edges) {
+            indices.push(vertices.len());
+            if required > 0 {
+                vertices.push((pos, required));
+            }
+        }
+        Self {
+            vertices,
+            solution: solution
+                .into_iter()
+                .map(|(i1, i2)| (indices[i1], indices[i2]))
+                .collect(),
+        }
+    }
+}
+
 pub fn generate_level(
     mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
+    meshes: Res<SharedMeshes>,
     mut materials: ResMut<Assets<VertexMaterial>>,
+    mut level_text_q: Query<&mut Transform, With<LevelText>>,
     level: Res<Level>,
+    settings: Res<Settings>,
+    mut history: ResMut<EdgeHistory>,
 ) {
-    let mut rng = StdRng::seed_from_u64(level.0);
+    history.clear();
+    let rules = LevelRules::for_level(level.0);
+    let puzzle = Puzzle::generate(level.0);
+    commands.insert_resource(LevelStats {
+        min_moves: puzzle.solution.len() as u32,
+        ..default()
+    });
 
-    let vertex_count =
-        rng.gen_range((1 + level.0 as usize).min(4)..=(1 + level.0 as usize).min(25));
+    let mut extent =
+        Rect::from_center_half_size(Vec2::ZERO, Vec2::splat(CameraView::MIN_VIEW_SIZE / 2.0));
+    let mut entities = Vec::with_capacity(puzzle.vertices.len());
+    for (i, (pos, required_edges)) in puzzle.vertices.iter().enumerate() {
+        extent = extent.union_point(*pos + Vertex::RADIUS + 50.0);
+        extent = extent.union_point(*pos - Vertex::RADIUS - 50.0);
+        entities.push(Vertex::new(*required_edges, *pos).spawn(
+            i as f32 / puzzle.vertices.len() as f32,
+            &settings,
+            &rules,
+            commands.reborrow(),
+            &meshes,
+            materials.reborrow(),
+        ));
+    }
+    commands.insert_resource(LevelSolution(
+        puzzle
+            .solution
+            .iter()
+            .map(|(i1, i2)| (entities[*i1], entities[*i2]))
+            .collect(),
+    ));
+
+    commands.insert_resource(rules);
+    commands.insert_resource(CameraView::framing(extent));
+    if let Ok(mut transform) = level_text_q.single_mut() {
+        transform.translation.y = extent.max.y - 30.0;
+    }
+}
+
+/// Vertices of a level get this many tries to find a free spot before the playfield counts
+/// as full.
+const MAX_TRIES: u32 = 1000;
+
+/// Places the vertices of a level at random, each one in range of an earlier one. Returns their
+/// positions, how many vertices the level has, and how many positions were tried.
+fn place_vertices(level: u64, rng: &mut StdRng) -> (Vec<Vec2>, usize, u32) {
+    let vertex_count = rng.gen_range(vertex_count_range(level));
     let mut positions = Vec::with_capacity(vertex_count);
     positions.push(Vec2::ZERO);
 
-    for _ in 0..vertex_count {
-        positions.push(loop {
-            // TODO: limit number of iterations
+    // Levels with more vertices than fit on the screen get a bigger playfield.
+    let half_extent = 620.0 * (vertex_count as f32 / 25.0).sqrt().max(1.0);
+
+    let mut attempts = 0;
+    'outer: for _ in 0..vertex_count {
+        for _ in 0..MAX_TRIES {
+            attempts += 1;
             let pos = (positions[rng.gen_range(0..positions.len())]
                 + rng.gen_range(Vertex::RADIUS * 2.0..Edge::MAX_LEN + Vertex::RADIUS * 2.0)
                     * Vec2::from_angle(rng.gen_range(-PI..PI)))
-            .clamp(Vec2::new(-620.0, -620.0), Vec2::new(620.0, 620.0));
+            .clamp(Vec2::splat(-half_extent), Vec2::splat(half_extent));
             const MIN_DIST: f32 = Vertex::RADIUS * 2.0 + 40.0;
             if positions
                 .iter()
                 .all(|p| p.distance_squared(pos) > MIN_DIST * MIN_DIST)
             {
-                break pos;
-            }
-        });
-    }
-
-    let mut required_edges = vec![0; vertex_count];
-    const EDGE_PROBABILITY: f32 = 0.5;
-
-    for (i1, pos1) in positions.iter().enumerate() {
-        for i2 in i1 + 1..vertex_count {
-            let pos2 = positions[i2];
-            let dist = pos1.distance(pos2);
-            if dist < Edge::MAX_LEN + Vertex::RADIUS * 2.0
-                && (required_edges[i1] == 0 || rng.r#gen::<f32>() < EDGE_PROBABILITY)
-                && {
-                    let dir = Dir2::new(pos2 - pos1).unwrap_or(Dir2::X);
-                    let ray_cast = RayCast2d::new(
-                        *pos1 + (Vertex::RADIUS + 0.1) * dir,
-                        dir,
-                        dist - 2.0 * (Vertex::RADIUS + 0.1),
-                    );
-                    let mut clear = true;
-                    for obstacle_pos in &positions {
-                        let circle = BoundingCircle::new(*obstacle_pos, Vertex::RADIUS);
-                        if ray_cast.circle_intersection_at(&circle).is_some() {
-                            clear = false;
-                            break;
-                        }
-                    }
-                    clear
-                }
-            {
-                required_edges[i1] += 1;
-                required_edges[i2] += 1;
+                positions.push(pos);
+                continue 'outer;
             }
         }
+        // The playfield is full.
+        break;
     }
+    let vertex_count = vertex_count.min(positions.len());
+    (positions, vertex_count, attempts)
+}
 
-    for (i, (pos, required_edges)) in positions.iter().zip(required_edges).enumerate() {
-        if required_edges == 0 {
-            continue;
-        }
-        Vertex::new(required_edges, *pos).spawn(
-            i as f32 / vertex_count as f32,
-            commands.reborrow(),
-            meshes.reborrow(),
-            materials.reborrow(),
-        );
+/// Levels up to this one grow quickly, later ones slowly but without a limit on their size.
+const LAST_EARLY_LEVEL: u64 = 24;
+
+fn vertex_count_range(level: u64) -> RangeInclusive<usize> {
+    if level <= LAST_EARLY_LEVEL {
+        return (1 + level as usize).min(4)..=1 + level as usize;
     }
+    let max = 25 + (level - LAST_EARLY_LEVEL) as usize / 2;
+    max * 2 / 3..=max
+}
+
+/// Probability of generating an edge between two vertices that already have edges.
+fn edge_probability(level: u64) -> f32 {
+    let late_levels = level.saturating_sub(LAST_EARLY_LEVEL) as f32;
+    (0.5 + late_levels * 0.002).min(0.7)
 }
 
 fn check_if_solved(
     vertex_q: Query<(Entity, &Vertex)>,
     mut next_state: ResMut<NextState<GameState>>,
-    beat_level_audio: Res<BeatLevelAudioHandle>,
+    sound_effects: SoundEffects,
+    level: Res<Level>,
+    stats: Res<LevelStats>,
+    mut progress: ResMut<Progress>,
     mut commands: Commands,
 ) {
     let solved = vertex_q
@@ -142,11 +332,37 @@ fn check_if_solved(
         .all(|(_, vertex)| vertex.edges.len() == vertex.required_edges);
     if solved {
         next_state.set(GameState::LevelExit);
-        commands.spawn((
-            AudioPlayer(beat_level_audio.0.clone()),
-            PlaybackSettings::DESPAWN,
-        ));
+        progress.record(
+            level.0,
+            LevelResult {
+                time: stats.time.elapsed(),
+                moves: stats.moves,
+                stars: stats.stars(),
+            },
+        );
+        commands.spawn(sound_effects.beat_level(PlaybackSettings::DESPAWN));
+    }
+}
+
+fn tick_level_stats(mut stats: ResMut<LevelStats>, time: Res<Time>) {
+    stats.time.tick(time.delta());
+}
+
+fn update_level_stats_text(
+    stats: Res<LevelStats>,
+    locale: Res<Locale>,
+    mut text: Single<&mut Text2d, With<LevelStatsText>>,
+) {
+    if !stats.is_changed() && !locale.is_changed() {
+        return;
     }
+    text.0 = locale.format(
+        "level-stats",
+        &[
+            ("time", &format_time(stats.time.elapsed())),
+            ("moves", &stats.moves),
+        ],
+    );
 }
 
 #[derive(Resource)]
@@ -154,41 +370,49 @@ struct NextLevelTimer(Timer);
 
 impl Default for NextLevelTimer {
     fn default() -> Self {
-        Self(Timer::from_seconds(2.0, TimerMode::Once))
+        Self(Timer::from_seconds(0.0, TimerMode::Once))
     }
 }
 
-fn enter_level(
-    mut timer: ResMut<NextLevelTimer>,
-    time: Res<Time>,
-    mut cam: Single<&mut Transform, With<Camera>>,
-    mut next_state: ResMut<NextState<GameState>>,
-) {
-    timer.0.tick(time.delta());
-    let t = timer.0.fraction();
-    let quadratic_out = -t * t + 2.0 * t - 1.0;
-    let cubic_out = (t - 1.0).powi(3);
-    cam.translation = Vec3::new(quadratic_out * 2000.0, cubic_out * -1000.0, 0.0);
-    if timer.0.finished() {
+/// Camera, fade overlay and settings used to play level transitions.
+#[derive(SystemParam)]
+struct TransitionView<'w> {
+    timer: ResMut<'w, NextLevelTimer>,
+    time: Res<'w, Time>,
+    cam: Single<'w, &'static mut Transform, With<Camera>>,
+    overlay: Single<'w, &'static mut BackgroundColor, With<FadeOverlay>>,
+    view: Res<'w, CameraView>,
+    settings: Res<'w, Settings>,
+}
+
+impl TransitionView<'_> {
+    /// Advances `transition`. Returns whether it is finished.
+    fn play(&mut self, transition: Transition) -> bool {
+        let timer = &mut self.timer.0;
+        timer.set_duration(Duration::from_secs_f32(transition.secs));
+        timer.tick(self.time.delta());
+        let (offset, fade) = transition.sample(timer.fraction());
+        self.cam.translation = (self.view.center + offset).extend(0.0);
+        self.overlay.0 = self.settings.theme.palette().background.with_alpha(fade);
+        if timer.finished() {
+            timer.reset();
+            return true;
+        }
+        false
+    }
+}
+
+fn enter_level(mut view: TransitionView, mut next_state: ResMut<NextState<GameState>>) {
+    let transition = Transition::level_enter(&view.settings);
+    if view.play(transition) {
         next_state.set(GameState::Playing);
-        timer.0.reset();
     }
 }
 
-fn exit_level(
-    mut timer: ResMut<NextLevelTimer>,
-    time: Res<Time>,
-    mut cam: Single<&mut Transform, With<Camera>>,
-    mut next_state: ResMut<NextState<GameState>>,
-) {
-    timer.0.tick(time.delta());
-    let t = timer.0.fraction();
-    let quadratic_in = t * t;
-    let cubic_in = t * t * t;
-    cam.translation = Vec3::new(quadratic_in * 2000.0, cubic_in * -1000.0, 0.0);
-    if timer.0.finished() {
+fn exit_level(mut view: TransitionView, mut next_state: ResMut<NextState<GameState>>) {
+    let transition = Transition::level_exit(&view.settings);
+    if view.play(transition) {
         next_state.set(GameState::LevelEnter);
-        timer.0.reset();
     }
 }
 
@@ -196,14 +420,57 @@ fn exit_level(
 fn switch_level(
     despawn_q: Query<Entity, Or<(With<Vertex>, With<Edge>)>>,
     mut level: ResMut<Level>,
-    mut level_text_q: Query<&mut Text2d, With<LevelText>>,
     mut commands: Commands,
 ) {
     for entity in &despawn_q {
         commands.entity(entity).despawn();
     }
     level.0 += 1;
-    if let Ok(mut level_text) = level_text_q.single_mut() {
-        level_text.0 = format!("Level {}", level.0);
+}
+
+/// Removes all edges by generating the level again.
+#[allow(clippy::type_complexity)]
+fn restart_level(despawn_q: Query<Entity, Or<(With<Vertex>, With<Edge>)>>, mut commands: Commands) {
+    for entity in &despawn_q {
+        commands.entity(entity).despawn();
+    }
+    commands.run_system_cached(generate_level);
+}
+
+fn update_level_text(
+    level: Res<Level>,
+    locale: Res<Locale>,
+    mut text: Single<&mut Text2d, With<LevelText>>,
+) {
+    text.0 = locale.format("level", &[("level", &level.0)]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn late_levels_place_vertices_without_many_retries() {
+        for level in [100, 500, 1000] {
+            let (_, vertex_count, attempts) =
+                place_vertices(level, &mut StdRng::seed_from_u64(level));
+            assert!(
+                attempts <= MAX_TRIES * vertex_count as u32,
+                "level {level} took {attempts} attempts for {vertex_count} vertices",
+            );
+        }
+    }
+
+    #[test]
+    fn vertex_count_grows_with_level() {
+        let mut last = 0;
+        for level in [25, 50, 100, 250, 500, 1000] {
+            let vertex_count = Puzzle::generate(level).vertices.len();
+            assert!(
+                vertex_count >= last,
+                "level {level} has {vertex_count} vertices, fewer than {last}"
+            );
+            last = vertex_count;
+        }
     }
 }
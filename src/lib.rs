@@ -0,0 +1,3 @@
+//! Parts of the game that don't need an app, shared with the benchmarks.
+
+pub mod spatial;
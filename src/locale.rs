@@ -0,0 +1,143 @@
+//! UI strings in several languages.
+//!
+//! The string tables in `assets/locales` use a small subset of the Fluent syntax: one
+//! `key = value` message per line, `#` comments and `{ $name }` placeables.
+
+use std::fmt::Display;
+
+use bevy::{platform::collections::HashMap, prelude::*};
+
+use crate::settings::Settings;
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<Locale>()
+        .add_systems(
+            PreUpdate,
+            load_language.run_if(resource_changed::<Settings>),
+        )
+        .add_systems(Update, update_localized_text);
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum Language {
+    #[default]
+    English,
+    German,
+    Spanish,
+}
+
+impl Language {
+    pub const ALL: [Self; 3] = [Self::English, Self::German, Self::Spanish];
+
+    /// Name of the language in itself.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::English => "English",
+            Self::German => "Deutsch",
+            Self::Spanish => "Español",
+        }
+    }
+
+    /// Name used in the settings file.
+    pub fn key(self) -> &'static str {
+        match self {
+            Self::English => "en",
+            Self::German => "de",
+            Self::Spanish => "es",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|language| language.key() == key)
+    }
+
+    pub fn next(self) -> Self {
+        let i = Self::ALL
+            .iter()
+            .position(|language| *language == self)
+            .unwrap();
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    fn source(self) -> &'static str {
+        match self {
+            Self::English => include_str!("../assets/locales/en.ftl"),
+            Self::German => include_str!("../assets/locales/de.ftl"),
+            Self::Spanish => include_str!("../assets/locales/es.ftl"),
+        }
+    }
+}
+
+/// Strings of the language chosen in the settings.
+#[derive(Resource)]
+pub struct Locale {
+    language: Language,
+    strings: HashMap<&'static str, &'static str>,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self::load(Language::default())
+    }
+}
+
+impl Locale {
+    fn load(language: Language) -> Self {
+        let mut strings = HashMap::new();
+        for language in [Language::English, language] {
+            for line in language.source().lines() {
+                let line = line.trim();
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                let Some((key, value)) = line.split_once('=') else {
+                    warn!("Skipping invalid line in {}.ftl: {line}", language.key());
+                    continue;
+                };
+                strings.insert(key.trim(), value.trim());
+            }
+        }
+        Self { language, strings }
+    }
+
+    /// Returns the string `key`, or `key` itself if no language has it.
+    pub fn get<'a>(&self, key: &'a str) -> &'a str {
+        self.strings.get(key).copied().unwrap_or(key)
+    }
+
+    /// Returns the string `key` with each `{ $name }` replaced by the matching argument.
+    pub fn format(&self, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        let mut text = self.get(key).to_string();
+        for (name, value) in args {
+            text = text.replace(&format!("{{ ${name} }}"), &value.to_string());
+        }
+        text
+    }
+}
+
+/// Sets the entity's text to the string `0` of the current language.
+#[derive(Component)]
+pub struct LocalizedText(pub &'static str);
+
+fn load_language(settings: Res<Settings>, mut locale: ResMut<Locale>) {
+    if locale.language != settings.language {
+        *locale = Locale::load(settings.language);
+    }
+}
+
+fn update_localized_text(
+    locale: Res<Locale>,
+    mut q: Query<(Ref<LocalizedText>, Option<&mut Text>, Option<&mut Text2d>)>,
+) {
+    for (localized, text, text_2d) in &mut q {
+        if !locale.is_changed() && !localized.is_added() {
+            continue;
+        }
+        let string = locale.get(localized.0).to_string();
+        if let Some(mut text) = text {
+            text.0 = string;
+        } else if let Some(mut text_2d) = text_2d {
+            text_2d.0 = string;
+        }
+    }
+}
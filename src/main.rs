@@ -1,22 +1,82 @@
 #![cfg_attr(not(feature = "console"), windows_subsystem = "windows")]
 
 use bevy::{core_pipeline::bloom::Bloom, prelude::*, render::camera::ScalingMode};
+use graph_game::spatial;
 
+mod accessibility;
 mod audio;
+mod book;
+mod camera;
 mod edge;
+mod effects;
+mod export;
+mod graphics;
+mod input;
 mod level;
+mod locale;
+mod meshes;
 mod pause;
+mod progress;
+mod settings;
+mod theme;
+mod transition;
 mod vertex;
 
 fn main() -> AppExit {
+    let args = std::env::args().collect::<Vec<_>>();
+    let flag = args.get(1).map(String::as_str);
+    if flag == Some("--export-sounds") {
+        let [_, _, dir] = &args[..] else {
+            eprintln!("Usage: --export-sounds DIR");
+            return AppExit::error();
+        };
+        return audio::export_sounds(dir.as_ref());
+    }
+    if let Some(flag @ ("--export-svg" | "--export-solution-svg")) = flag {
+        let [_, _, level, path] = &args[..] else {
+            eprintln!("Usage: {flag} LEVEL FILE");
+            return AppExit::error();
+        };
+        let Ok(level) = level.parse() else {
+            eprintln!("Invalid level: {level}");
+            return AppExit::error();
+        };
+        return export::export_svg(level, path.as_ref(), flag == "--export-solution-svg");
+    }
+    if flag == Some("--book") {
+        let [_, _, count, level, dir] = &args[..] else {
+            eprintln!("{}", book::USAGE);
+            return AppExit::error();
+        };
+        let (Ok(count), Ok(level)) = (count.parse(), level.parse()) else {
+            eprintln!("{}", book::USAGE);
+            return AppExit::error();
+        };
+        return book::write_book(count, level, dir.as_ref());
+    }
+
     App::new()
         .add_plugins((
             DefaultPlugins,
             MeshPickingPlugin,
+            export::plugin,
+            meshes::plugin,
+        ))
+        .add_plugins((
+            accessibility::plugin,
             audio::plugin,
+            camera::plugin,
             edge::plugin,
+            effects::plugin,
+            graphics::plugin,
+            input::plugin,
             level::plugin,
+            locale::plugin,
             pause::plugin,
+            progress::plugin,
+            settings::plugin,
+            theme::plugin,
+            transition::plugin,
             vertex::plugin,
         ))
         .init_state::<GameState>()
@@ -40,6 +100,7 @@ fn setup(mut commands: Commands) {
             ..default()
         },
         Bloom::NATURAL,
+        SpatialListener::new(audio::LISTENER_GAP),
     ));
 }
 
@@ -50,6 +111,8 @@ enum GameState {
     Paused,
     LevelSelect,
     Settings,
+    Graphics,
+    Controls,
     #[default]
     LevelEnter,
     LevelExit,
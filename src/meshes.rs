@@ -0,0 +1,25 @@
+use bevy::prelude::*;
+
+pub fn plugin(app: &mut App) {
+    app.init_resource::<SharedMeshes>();
+}
+
+/// Meshes that all entities of a shape share. They are sized through `Transform::scale`, so
+/// moving or resizing an entity never changes mesh data.
+#[derive(Resource)]
+pub struct SharedMeshes {
+    /// Circle with a radius of 1.
+    pub circle: Handle<Mesh>,
+    /// Square with a side length of 1.
+    pub quad: Handle<Mesh>,
+}
+
+impl FromWorld for SharedMeshes {
+    fn from_world(world: &mut World) -> Self {
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        Self {
+            circle: meshes.add(Circle::new(1.0)),
+            quad: meshes.add(Rectangle::new(1.0, 1.0)),
+        }
+    }
+}
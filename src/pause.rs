@@ -1,26 +1,39 @@
-use bevy::{input::common_conditions::input_just_pressed, prelude::*, window::PrimaryWindow};
+use bevy::{prelude::*, window::PrimaryWindow};
 
-use crate::GameState::{self, *};
+use crate::{
+    GameState::{self, *},
+    input::{Action, action_just_pressed},
+    locale::LocalizedText,
+    settings::Settings,
+    theme::ThemeColor,
+};
 
+mod controls;
+mod graphics;
 mod levels;
 mod settings;
 
 pub fn plugin(app: &mut App) {
-    app.add_plugins((levels::plugin, settings::plugin))
-        .add_systems(
-            Update,
-            (
-                pause.run_if(input_just_pressed(KeyCode::Escape)),
-                update_ui_scale,
-                update_buttons,
-            ),
-        )
-        .add_systems(OnEnter(Paused), setup);
+    app.add_plugins((
+        controls::plugin,
+        graphics::plugin,
+        levels::plugin,
+        settings::plugin,
+    ))
+    .add_systems(
+        Update,
+        (
+            pause.run_if(action_just_pressed(Action::Pause)),
+            update_ui_scale,
+            update_buttons,
+        ),
+    )
+    .add_systems(OnEnter(Paused), setup);
 }
 
 fn pause(state: Res<State<GameState>>, mut next_state: ResMut<NextState<GameState>>) {
     next_state.set(match state.get() {
-        Playing | LevelSelect | Settings => Paused,
+        Playing | LevelSelect | Settings | Graphics | Controls => Paused,
         Paused => Playing,
         LevelEnter | LevelExit => return,
     });
@@ -30,6 +43,8 @@ fn pause(state: Res<State<GameState>>, mut next_state: ResMut<NextState<GameStat
 enum ButtonType {
     Levels,
     Settings,
+    Graphics,
+    Controls,
     Exit,
 }
 
@@ -43,25 +58,27 @@ fn setup(mut commands: Commands) {
             align_items: AlignItems::Center,
             ..default()
         },
-        BackgroundColor(Color::WHITE),
+        ThemeColor::Panel,
         children![
             (
-                Text::new("Paused"),
+                Text::default(),
+                LocalizedText("paused"),
                 TextFont {
                     font_size: 60.0,
                     ..default()
                 },
-                TextColor(Color::BLACK),
+                ThemeColor::PanelText,
             ),
             (
                 Button,
                 ButtonType::Levels,
-                Text::new("Levels"),
+                Text::default(),
+                LocalizedText("levels"),
                 TextFont {
                     font_size: 50.0,
                     ..default()
                 },
-                TextColor(Color::BLACK),
+                ThemeColor::PanelText,
                 Node {
                     top: Val::Percent(30.0),
                     ..default()
@@ -70,12 +87,13 @@ fn setup(mut commands: Commands) {
             (
                 Button,
                 ButtonType::Settings,
-                Text::new("Settings"),
+                Text::default(),
+                LocalizedText("settings"),
                 TextFont {
                     font_size: 50.0,
                     ..default()
                 },
-                TextColor(Color::BLACK),
+                ThemeColor::PanelText,
                 Node {
                     top: Val::Percent(40.0),
                     ..default()
@@ -83,17 +101,48 @@ fn setup(mut commands: Commands) {
             ),
             (
                 Button,
-                ButtonType::Exit,
-                Text::new("Exit"),
+                ButtonType::Graphics,
+                Text::default(),
+                LocalizedText("graphics"),
                 TextFont {
                     font_size: 50.0,
                     ..default()
                 },
-                TextColor(Color::BLACK),
+                ThemeColor::PanelText,
                 Node {
                     top: Val::Percent(50.0),
                     ..default()
                 }
+            ),
+            (
+                Button,
+                ButtonType::Controls,
+                Text::default(),
+                LocalizedText("controls"),
+                TextFont {
+                    font_size: 50.0,
+                    ..default()
+                },
+                ThemeColor::PanelText,
+                Node {
+                    top: Val::Percent(60.0),
+                    ..default()
+                }
+            ),
+            (
+                Button,
+                ButtonType::Exit,
+                Text::default(),
+                LocalizedText("exit"),
+                TextFont {
+                    font_size: 50.0,
+                    ..default()
+                },
+                ThemeColor::PanelText,
+                Node {
+                    top: Val::Percent(70.0),
+                    ..default()
+                }
             )
         ],
     ));
@@ -103,6 +152,7 @@ fn update_buttons(
     mut q: Query<(&Interaction, &ButtonType, &mut BackgroundColor), Changed<Interaction>>,
     mut exit_evw: EventWriter<AppExit>,
     mut next_state: ResMut<NextState<GameState>>,
+    settings: Res<Settings>,
 ) {
     use ButtonType::*;
     use Interaction::*;
@@ -112,13 +162,15 @@ fn update_buttons(
                 bg.0 = Color::NONE;
             }
             Hovered => {
-                bg.0 = Color::srgb(0.8, 0.8, 0.8);
+                bg.0 = settings.theme.palette().hovered();
             }
             Pressed => {
-                bg.0 = Color::srgb(0.6, 0.6, 0.6);
+                bg.0 = settings.theme.palette().pressed();
                 match *button_type {
                     Levels => next_state.set(GameState::LevelSelect),
                     Settings => next_state.set(GameState::Settings),
+                    Graphics => next_state.set(GameState::Graphics),
+                    Controls => next_state.set(GameState::Controls),
                     Exit => {
                         exit_evw.write(AppExit::Success);
                     }
@@ -0,0 +1,191 @@
+use bevy::prelude::*;
+
+use crate::{
+    GameState,
+    input::{Action, BindingSlot, Bindings, Rebinding, button_name, key_name},
+    locale::{Locale, LocalizedText},
+    settings::Settings,
+    theme::ThemeColor,
+};
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (
+            update_binding_buttons,
+            update_reset_button,
+            update_binding_texts.run_if(
+                resource_changed::<Settings>
+                    .or(resource_changed::<Rebinding>)
+                    .or(resource_changed::<Locale>),
+            ),
+        )
+            .run_if(in_state(GameState::Controls)),
+    )
+    .add_systems(OnEnter(GameState::Controls), setup)
+    .add_systems(OnExit(GameState::Controls), cancel_rebinding);
+}
+
+/// Shows the key or gamepad button bound to an action and rebinds it when pressed.
+#[derive(Component, Clone, Copy)]
+struct BindingButton {
+    action: Action,
+    slot: BindingSlot,
+}
+
+impl BindingButton {
+    fn text(self, settings: &Settings, rebinding: &Rebinding) -> String {
+        if rebinding.0 == Some((self.action, self.slot)) {
+            return "...".to_string();
+        }
+        let binding = settings.bindings.get(self.action);
+        match self.slot {
+            BindingSlot::Key => key_name(binding.key),
+            BindingSlot::Button => button_name(binding.button),
+        }
+    }
+}
+
+#[derive(Component)]
+struct ResetButton;
+
+fn font() -> TextFont {
+    TextFont {
+        font_size: 40.0,
+        ..default()
+    }
+}
+
+fn setup(mut commands: Commands, settings: Res<Settings>, rebinding: Res<Rebinding>) {
+    commands
+        .spawn((
+            StateScoped(GameState::Controls),
+            Node {
+                width: Val::Percent(45.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ThemeColor::Panel,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::default(),
+                LocalizedText("controls"),
+                TextFont {
+                    font_size: 60.0,
+                    ..default()
+                },
+                ThemeColor::PanelText,
+                Node {
+                    margin: UiRect::bottom(Val::Percent(15.0)),
+                    ..default()
+                },
+            ));
+            for action in Action::ALL {
+                let button = |slot| {
+                    let button = BindingButton { action, slot };
+                    (
+                        Button,
+                        button,
+                        Text(button.text(&settings, &rebinding)),
+                        font(),
+                        ThemeColor::PanelText,
+                    )
+                };
+                parent.spawn((
+                    Node {
+                        width: Val::Percent(90.0),
+                        margin: UiRect::top(Val::Px(20.0)),
+                        column_gap: Val::Px(30.0),
+                        ..default()
+                    },
+                    children![
+                        (
+                            Text::default(),
+                            LocalizedText(action.name_key()),
+                            font(),
+                            ThemeColor::PanelText,
+                            Node {
+                                flex_grow: 1.0,
+                                ..default()
+                            },
+                        ),
+                        button(BindingSlot::Key),
+                        button(BindingSlot::Button),
+                    ],
+                ));
+            }
+            parent.spawn((
+                Button,
+                ResetButton,
+                Text::default(),
+                LocalizedText("reset-controls"),
+                font(),
+                ThemeColor::PanelText,
+                Node {
+                    margin: UiRect::top(Val::Px(60.0)),
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn update_binding_buttons(
+    mut q: Query<(&Interaction, &BindingButton, &mut BackgroundColor), Changed<Interaction>>,
+    mut rebinding: ResMut<Rebinding>,
+    settings: Res<Settings>,
+) {
+    use Interaction::*;
+    for (interaction, button, mut bg) in &mut q {
+        match *interaction {
+            None => {
+                bg.0 = Color::NONE;
+            }
+            Hovered => {
+                bg.0 = settings.theme.palette().hovered();
+            }
+            Pressed => {
+                bg.0 = settings.theme.palette().pressed();
+                rebinding.0 = Some((button.action, button.slot));
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_reset_button(
+    mut q: Query<(&Interaction, &mut BackgroundColor), (Changed<Interaction>, With<ResetButton>)>,
+    mut settings: ResMut<Settings>,
+) {
+    use Interaction::*;
+    for (interaction, mut bg) in &mut q {
+        match *interaction {
+            None => {
+                bg.0 = Color::NONE;
+            }
+            Hovered => {
+                bg.0 = settings.theme.palette().hovered();
+            }
+            Pressed => {
+                bg.0 = settings.theme.palette().pressed();
+                settings.bindings = Bindings::default();
+            }
+        }
+    }
+}
+
+fn update_binding_texts(
+    mut q: Query<(&BindingButton, &mut Text)>,
+    settings: Res<Settings>,
+    rebinding: Res<Rebinding>,
+) {
+    for (button, mut text) in &mut q {
+        text.0 = button.text(&settings, &rebinding);
+    }
+}
+
+fn cancel_rebinding(mut rebinding: ResMut<Rebinding>) {
+    rebinding.0 = None;
+}
@@ -0,0 +1,187 @@
+use bevy::{prelude::*, window::Monitor};
+
+use crate::{
+    GameState,
+    graphics::{GraphicsSettings, sorted_monitors},
+    locale::{Locale, LocalizedText},
+    settings::Settings,
+    theme::ThemeColor,
+};
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (
+            update_buttons,
+            update_texts.run_if(resource_changed::<Settings>.or(resource_changed::<Locale>)),
+        )
+            .run_if(in_state(GameState::Graphics)),
+    )
+    .add_systems(OnEnter(GameState::Graphics), setup);
+}
+
+/// A button that changes a graphics setting when pressed and shows its current value.
+#[derive(Component, Clone, Copy)]
+enum GraphicsButton {
+    ScreenMode,
+    Monitor,
+    Resolution,
+    Vsync,
+    Msaa,
+    Bloom,
+}
+
+impl GraphicsButton {
+    const ALL: [Self; 6] = [
+        Self::ScreenMode,
+        Self::Monitor,
+        Self::Resolution,
+        Self::Vsync,
+        Self::Msaa,
+        Self::Bloom,
+    ];
+
+    fn press(self, graphics: &mut GraphicsSettings, monitors: &[(Entity, &Monitor)]) {
+        match self {
+            Self::ScreenMode => graphics.screen_mode = graphics.screen_mode.next(),
+            Self::Monitor => {
+                graphics.monitor = (graphics.monitor + 1) % monitors.len().max(1);
+                // The resolutions of the old monitor may not exist on the new one.
+                graphics.resolution = None;
+            }
+            Self::Resolution => {
+                let monitor = monitors.get(graphics.monitor).map(|(_, monitor)| *monitor);
+                graphics.next_resolution(monitor);
+            }
+            Self::Vsync => graphics.vsync = !graphics.vsync,
+            Self::Msaa => graphics.msaa = !graphics.msaa,
+            Self::Bloom => graphics.bloom = graphics.bloom.next(),
+        }
+    }
+
+    fn text(
+        self,
+        graphics: &GraphicsSettings,
+        locale: &Locale,
+        monitors: &[(Entity, &Monitor)],
+    ) -> String {
+        let checkbox =
+            |key, value| format!("{} [{}]", locale.get(key), if value { "X" } else { " " });
+        match self {
+            Self::ScreenMode => locale.format(
+                "screen-mode",
+                &[("mode", &locale.get(graphics.screen_mode.name_key()))],
+            ),
+            Self::Monitor => {
+                let monitor = match monitors.get(graphics.monitor) {
+                    Some((
+                        _,
+                        Monitor {
+                            name: Some(name), ..
+                        },
+                    )) => {
+                        format!("{} ({name})", graphics.monitor + 1)
+                    }
+                    _ => format!("{}", graphics.monitor + 1),
+                };
+                locale.format("monitor", &[("monitor", &monitor)])
+            }
+            Self::Resolution => {
+                let resolution = match graphics.resolution {
+                    Some(size) => format!("{}x{}", size.x, size.y),
+                    None => locale.get("resolution-native").to_string(),
+                };
+                locale.format("resolution", &[("resolution", &resolution)])
+            }
+            Self::Vsync => checkbox("vsync", graphics.vsync),
+            Self::Msaa => checkbox("msaa", graphics.msaa),
+            Self::Bloom => locale.format(
+                "bloom",
+                &[("bloom", &locale.get(graphics.bloom.name_key()))],
+            ),
+        }
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    monitor_q: Query<(Entity, &Monitor)>,
+) {
+    let monitors = sorted_monitors(&monitor_q);
+    commands
+        .spawn((
+            StateScoped(GameState::Graphics),
+            Node {
+                width: Val::Percent(30.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ThemeColor::Panel,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::default(),
+                LocalizedText("graphics"),
+                TextFont {
+                    font_size: 60.0,
+                    ..default()
+                },
+                ThemeColor::PanelText,
+            ));
+            for button in GraphicsButton::ALL {
+                parent.spawn((
+                    Button,
+                    button,
+                    Text(button.text(&settings.graphics, &locale, &monitors)),
+                    TextFont {
+                        font_size: 50.0,
+                        ..default()
+                    },
+                    ThemeColor::PanelText,
+                    Node {
+                        top: Val::Percent(20.0),
+                        margin: UiRect::top(Val::Px(20.0)),
+                        ..default()
+                    },
+                ));
+            }
+        });
+}
+
+fn update_buttons(
+    mut q: Query<(&Interaction, &GraphicsButton, &mut BackgroundColor), Changed<Interaction>>,
+    monitor_q: Query<(Entity, &Monitor)>,
+    mut settings: ResMut<Settings>,
+) {
+    use Interaction::*;
+    for (interaction, button, mut bg) in &mut q {
+        match *interaction {
+            None => {
+                bg.0 = Color::NONE;
+            }
+            Hovered => {
+                bg.0 = settings.theme.palette().hovered();
+            }
+            Pressed => {
+                button.press(&mut settings.graphics, &sorted_monitors(&monitor_q));
+                bg.0 = settings.theme.palette().pressed();
+            }
+        }
+    }
+}
+
+fn update_texts(
+    mut q: Query<(&GraphicsButton, &mut Text)>,
+    monitor_q: Query<(Entity, &Monitor)>,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+) {
+    let monitors = sorted_monitors(&monitor_q);
+    for (button, mut text) in &mut q {
+        text.0 = button.text(&settings.graphics, &locale, &monitors);
+    }
+}
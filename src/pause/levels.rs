@@ -2,8 +2,14 @@ use bevy::prelude::*;
 
 use crate::{
     GameState,
-    edge::Edge,
-    level::{Level, LevelText, generate_level},
+    edge::{Edge, EdgeMaterial},
+    input::{Action, Actions},
+    level::{Level, generate_level},
+    locale::Locale,
+    meshes::SharedMeshes,
+    progress::{Progress, format_time},
+    settings::Settings,
+    theme::ThemeColor,
     vertex::{Vertex, VertexMaterial},
 };
 
@@ -18,27 +24,38 @@ pub fn plugin(app: &mut App) {
 #[derive(Component)]
 struct LevelVertex(u64);
 
+#[allow(clippy::too_many_arguments)]
 fn setup(
     mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
+    meshes: Res<SharedMeshes>,
     mut color_materials: ResMut<Assets<ColorMaterial>>,
+    mut edge_materials: ResMut<Assets<EdgeMaterial>>,
     mut vertex_materials: ResMut<Assets<VertexMaterial>>,
     level: Res<Level>,
+    progress: Res<Progress>,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
 ) {
     commands
         .spawn((
             StateScoped(GameState::LevelSelect),
-            Mesh2d(meshes.add(Rectangle::new(1440.0, 1440.0))),
-            MeshMaterial2d(color_materials.add(Color::BLACK)),
             Transform::from_xyz(0.0, 0.0, 1.0),
+            Visibility::default(),
         ))
         .with_children(|parent| {
             parent.spawn((
-                Text2d::new("Levels"),
+                Mesh2d(meshes.quad.clone()),
+                MeshMaterial2d(color_materials.add(ColorMaterial::default())),
+                ThemeColor::Background,
+                Transform::from_scale(Vec3::new(1440.0, 1440.0, 1.0)),
+            ));
+            parent.spawn((
+                Text2d(locale.format("levels-header", &[("stars", &progress.total_stars())])),
                 TextFont {
                     font_size: 60.0,
                     ..default()
                 },
+                ThemeColor::Foreground,
                 Transform::from_xyz(0.0, 690.0, 0.0),
             ));
 
@@ -59,14 +76,15 @@ fn setup(
 
             for i in page * 10 + 1..=page * 10 + 10 {
                 let pos = i_to_pos(i);
+                let mut material = VertexMaterial::new(&settings);
+                let mut text_color = TextColor::default();
+                material.set_solved(i < level.0, &mut text_color);
                 parent
                     .spawn((
                         LevelVertex(i),
-                        Mesh2d(meshes.add(Circle::new(Vertex::RADIUS))),
-                        MeshMaterial2d(vertex_materials.add(VertexMaterial {
-                            bits: if i < level.0 { 2 } else { 0 },
-                        })),
-                        Transform::from_translation(pos.extend(2.0)),
+                        Mesh2d(meshes.circle.clone()),
+                        MeshMaterial2d(vertex_materials.add(material)),
+                        Transform::from_translation(pos.extend(2.0)).with_scale(Vertex::SCALE),
                     ))
                     .with_child((
                         Text2d(format!("{i}")),
@@ -74,43 +92,75 @@ fn setup(
                             font_size: 70.0,
                             ..default()
                         },
-                        TextColor(if i < level.0 {
-                            Color::BLACK
-                        } else {
-                            Color::WHITE
-                        }),
+                        text_color,
+                        Vertex::child_transform(Vec2::ZERO),
+                    ))
+                    .with_child((
+                        Text2d(best_text(&progress, &locale, i)),
+                        TextFont {
+                            font_size: 30.0,
+                            ..default()
+                        },
+                        ThemeColor::Foreground,
+                        Vertex::child_transform(Vec2::new(0.0, -80.0)),
+                    ))
+                    .with_child((
+                        Text2d(stars_text(&progress, i)),
+                        TextFont {
+                            font_size: 40.0,
+                            ..default()
+                        },
+                        ThemeColor::Star,
+                        Vertex::child_transform(Vec2::new(0.0, 75.0)),
                     ))
                     .observe(handle_vertex_click);
                 if (i - 1) % 10 == 9 {
                     continue;
                 }
                 let next_pos = i_to_pos(i + 1);
-                parent.spawn((
-                    Mesh2d(meshes.add(Rectangle::new(DIST, Edge::WIDTH))),
-                    MeshMaterial2d(color_materials.add(Color::WHITE)),
-                    Transform {
-                        translation: ((pos + next_pos) / 2.0).extend(1.0),
-                        rotation: {
-                            let diff = next_pos - pos;
-                            Quat::from_rotation_z(diff.y.atan2(diff.x))
-                        },
-                        ..default()
-                    },
+                parent.spawn(Edge::bundle(
+                    pos,
+                    next_pos,
+                    1.0,
+                    EdgeMaterial::new(&settings),
+                    &meshes,
+                    &mut edge_materials,
                 ));
             }
         });
 }
 
+/// Best time and moves of `level`, or nothing if it hasn't been beaten yet.
+fn best_text(progress: &Progress, locale: &Locale, level: u64) -> String {
+    match progress.best(level) {
+        Some(best) => locale.format(
+            "best",
+            &[("time", &format_time(best.time)), ("moves", &best.moves)],
+        ),
+        None => String::new(),
+    }
+}
+
+/// One `*` per star `level` was beaten with.
+fn stars_text(progress: &Progress, level: u64) -> String {
+    match progress.best(level) {
+        Some(best) => "*".repeat(best.stars as usize),
+        None => String::new(),
+    }
+}
+
 fn handle_arrows(
-    kb: Res<ButtonInput<KeyCode>>,
+    actions: Actions,
     mut level_vertex_q: Query<(&mut LevelVertex, &MeshMaterial2d<VertexMaterial>, &Children)>,
     mut text_q: Query<(&mut Text2d, &mut TextColor)>,
     mut vertex_materials: ResMut<Assets<VertexMaterial>>,
     level: Res<Level>,
+    progress: Res<Progress>,
+    locale: Res<Locale>,
 ) -> Result {
-    let dir = if kb.just_pressed(KeyCode::ArrowLeft) {
+    let dir = if actions.just_pressed(Action::PagePrev) {
         -1
-    } else if kb.just_pressed(KeyCode::ArrowRight) {
+    } else if actions.just_pressed(Action::PageNext) {
         1
     } else {
         return Ok(());
@@ -126,6 +176,8 @@ fn handle_arrows(
             .get_mut(mesh_material)
             .ok_or("Invalid vertex material handle")?;
         material.set_solved(level_vertex.0 < level.0, &mut color);
+        text_q.get_mut(children[1])?.0.0 = best_text(&progress, &locale, level_vertex.0);
+        text_q.get_mut(children[2])?.0.0 = stars_text(&progress, level_vertex.0);
     }
     Ok(())
 }
@@ -136,7 +188,6 @@ fn handle_vertex_click(
     despawn_q: Query<Entity, Or<(With<Vertex>, With<Edge>)>>,
     level_vertex_q: Query<&LevelVertex>,
     mut level: ResMut<Level>,
-    mut level_text: Single<&mut Text2d, With<LevelText>>,
     mut next_state: ResMut<NextState<GameState>>,
     mut commands: Commands,
 ) -> Result {
@@ -144,7 +195,6 @@ fn handle_vertex_click(
         commands.entity(entity).despawn();
     }
     level.0 = level_vertex_q.get(trigger.target())?.0;
-    level_text.0 = format!("Level {}", level.0);
     next_state.set(GameState::Playing);
     commands.run_system_cached(generate_level);
     Ok(())
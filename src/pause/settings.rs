@@ -1,90 +1,266 @@
-use bevy::{
-    prelude::*,
-    window::{PrimaryWindow, WindowMode},
-};
+use bevy::prelude::*;
 
-use crate::GameState;
+use crate::{
+    GameState,
+    locale::{Locale, LocalizedText},
+    settings::Settings,
+    theme::ThemeColor,
+};
 
 pub fn plugin(app: &mut App) {
-    app.add_systems(Update, update_fullscreen)
-        .add_systems(OnEnter(GameState::Settings), setup);
+    app.add_systems(
+        Update,
+        (
+            update_setting_buttons,
+            update_volume_buttons,
+            update_texts.run_if(resource_changed::<Settings>.or(resource_changed::<Locale>)),
+        ),
+    )
+    .add_systems(OnEnter(GameState::Settings), setup);
+}
+
+/// A button that changes a setting when pressed and shows its current value.
+#[derive(Component, Clone, Copy)]
+enum SettingButton {
+    Theme,
+    Language,
+    ShapeCues,
+    ReducedMotion,
+    Effects,
+    Mute,
+}
+
+impl SettingButton {
+    const ALL: [Self; 6] = [
+        Self::Theme,
+        Self::Language,
+        Self::ShapeCues,
+        Self::ReducedMotion,
+        Self::Effects,
+        Self::Mute,
+    ];
+
+    fn press(self, settings: &mut Settings) {
+        match self {
+            Self::Theme => settings.theme = settings.theme.next(),
+            Self::Language => settings.language = settings.language.next(),
+            Self::ShapeCues => settings.shape_cues = !settings.shape_cues,
+            Self::ReducedMotion => settings.reduced_motion = !settings.reduced_motion,
+            Self::Effects => settings.effects = !settings.effects,
+            Self::Mute => settings.audio.muted = !settings.audio.muted,
+        }
+    }
+
+    fn text(self, settings: &Settings, locale: &Locale) -> String {
+        let checkbox =
+            |key, value| format!("{} [{}]", locale.get(key), if value { "X" } else { " " });
+        match self {
+            Self::Theme => locale.format(
+                "theme",
+                &[("theme", &locale.get(settings.theme.name_key()))],
+            ),
+            Self::Language => locale.format("language", &[("language", &settings.language.name())]),
+            Self::ShapeCues => checkbox("shape-cues", settings.shape_cues),
+            Self::ReducedMotion => checkbox("reduced-motion", settings.reduced_motion),
+            Self::Effects => checkbox("effects", settings.effects),
+            Self::Mute => checkbox("mute", settings.audio.muted),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum VolumeChannel {
+    Master,
+    Effects,
+    Music,
+}
+
+impl VolumeChannel {
+    const ALL: [Self; 3] = [Self::Master, Self::Effects, Self::Music];
+
+    /// Key of the channel's name in the string tables.
+    fn name_key(self) -> &'static str {
+        match self {
+            Self::Master => "volume-master",
+            Self::Effects => "volume-effects",
+            Self::Music => "volume-music",
+        }
+    }
+
+    fn volume(self, settings: &Settings) -> f32 {
+        match self {
+            Self::Master => settings.audio.master,
+            Self::Effects => settings.audio.effects,
+            Self::Music => settings.audio.music,
+        }
+    }
+
+    fn volume_mut(self, settings: &mut Settings) -> &mut f32 {
+        match self {
+            Self::Master => &mut settings.audio.master,
+            Self::Effects => &mut settings.audio.effects,
+            Self::Music => &mut settings.audio.music,
+        }
+    }
+}
+
+#[derive(Component)]
+struct VolumeButton {
+    channel: VolumeChannel,
+    step: f32,
 }
 
 #[derive(Component)]
-struct FullscreenButton;
-
-fn setup(mut commands: Commands, window: Single<&Window, With<PrimaryWindow>>) {
-    use WindowMode::*;
-    commands.spawn((
-        StateScoped(GameState::Settings),
-        Node {
-            width: Val::Percent(30.0),
-            height: Val::Percent(100.0),
-            flex_direction: FlexDirection::Column,
-            align_items: AlignItems::Center,
-            ..default()
-        },
-        BackgroundColor(Color::WHITE),
+struct VolumeText(VolumeChannel);
+
+fn volume_text(channel: VolumeChannel, settings: &Settings, locale: &Locale) -> String {
+    format!(
+        "{} {:>3}%",
+        locale.get(channel.name_key()),
+        (channel.volume(settings) * 100.0).round()
+    )
+}
+
+fn font() -> TextFont {
+    TextFont {
+        font_size: 50.0,
+        ..default()
+    }
+}
+
+fn row_node() -> Node {
+    Node {
+        top: Val::Percent(20.0),
+        margin: UiRect::top(Val::Px(20.0)),
+        column_gap: Val::Px(20.0),
+        ..default()
+    }
+}
+
+fn volume_row(channel: VolumeChannel, settings: &Settings, locale: &Locale) -> impl Bundle {
+    (
+        row_node(),
         children![
             (
-                Text::new("Settings"),
-                TextFont {
-                    font_size: 60.0,
-                    ..default()
+                Button,
+                VolumeButton {
+                    channel,
+                    step: -0.1,
                 },
-                TextColor(Color::BLACK),
+                Text::new("-"),
+                font(),
+                ThemeColor::PanelText,
+            ),
+            (
+                VolumeText(channel),
+                Text(volume_text(channel, settings, locale)),
+                font(),
+                ThemeColor::PanelText,
             ),
             (
                 Button,
-                FullscreenButton,
-                Text(format!(
-                    "Fullscreen [{}]",
-                    match window.mode {
-                        Windowed => " ",
-                        BorderlessFullscreen(_) | Fullscreen(..) => "X",
-                    }
-                )),
+                VolumeButton { channel, step: 0.1 },
+                Text::new("+"),
+                font(),
+                ThemeColor::PanelText,
+            ),
+        ],
+    )
+}
+
+fn setup(mut commands: Commands, settings: Res<Settings>, locale: Res<Locale>) {
+    commands
+        .spawn((
+            StateScoped(GameState::Settings),
+            Node {
+                width: Val::Percent(30.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ThemeColor::Panel,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::default(),
+                LocalizedText("settings"),
                 TextFont {
-                    font_size: 50.0,
+                    font_size: 60.0,
                     ..default()
                 },
-                TextColor(Color::BLACK),
-                Node {
-                    top: Val::Percent(35.0),
-                    ..default()
-                }
-            ),
-        ],
-    ));
+                ThemeColor::PanelText,
+            ));
+            for button in SettingButton::ALL {
+                parent.spawn((
+                    Button,
+                    button,
+                    Text(button.text(&settings, &locale)),
+                    font(),
+                    ThemeColor::PanelText,
+                    row_node(),
+                ));
+            }
+            for channel in VolumeChannel::ALL {
+                parent.spawn(volume_row(channel, &settings, &locale));
+            }
+        });
+}
+
+fn update_setting_buttons(
+    mut q: Query<(&Interaction, &SettingButton, &mut BackgroundColor), Changed<Interaction>>,
+    mut settings: ResMut<Settings>,
+) {
+    use Interaction::*;
+    for (interaction, button, mut bg) in &mut q {
+        match *interaction {
+            None => {
+                bg.0 = Color::NONE;
+            }
+            Hovered => {
+                bg.0 = settings.theme.palette().hovered();
+            }
+            Pressed => {
+                button.press(&mut settings);
+                bg.0 = settings.theme.palette().pressed();
+            }
+        }
+    }
 }
 
-#[allow(clippy::type_complexity)]
-fn update_fullscreen(
-    mut q: Query<
-        (&Interaction, &mut BackgroundColor, &mut Text),
-        (Changed<Interaction>, With<FullscreenButton>),
-    >,
-    mut window: Single<&mut Window, With<PrimaryWindow>>,
+fn update_volume_buttons(
+    mut q: Query<(&Interaction, &VolumeButton, &mut BackgroundColor), Changed<Interaction>>,
+    mut settings: ResMut<Settings>,
 ) {
     use Interaction::*;
-    for (interaction, mut bg, mut text) in &mut q {
+    for (interaction, button, mut bg) in &mut q {
         match *interaction {
             None => {
                 bg.0 = Color::NONE;
             }
             Hovered => {
-                bg.0 = Color::srgb(0.8, 0.8, 0.8);
+                bg.0 = settings.theme.palette().hovered();
             }
             Pressed => {
-                bg.0 = Color::srgb(0.6, 0.6, 0.6);
-                use WindowMode::*;
-                let (mode, str) = match window.mode {
-                    Windowed => (BorderlessFullscreen(MonitorSelection::Current), "X"),
-                    BorderlessFullscreen(_) | Fullscreen(..) => (Windowed, " "),
-                };
-                window.mode = mode;
-                text.replace_range(12..13, str);
+                bg.0 = settings.theme.palette().pressed();
+                let volume = button.channel.volume_mut(&mut settings);
+                *volume = (*volume + button.step).clamp(0.0, 1.0);
             }
         }
     }
 }
+
+/// Rebuilds the button texts when a setting or the language changes.
+fn update_texts(
+    mut button_q: Query<(&SettingButton, &mut Text), Without<VolumeText>>,
+    mut volume_q: Query<(&VolumeText, &mut Text)>,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+) {
+    for (button, mut text) in &mut button_q {
+        text.0 = button.text(&settings, &locale);
+    }
+    for (volume_text, mut text) in &mut volume_q {
+        text.0 = self::volume_text(volume_text.0, &settings, &locale);
+    }
+}
@@ -0,0 +1,97 @@
+use std::{fs, time::Duration};
+
+use bevy::{platform::collections::HashMap, prelude::*};
+
+pub fn plugin(app: &mut App) {
+    app.insert_resource(Progress::load());
+}
+
+/// Best result of a level, with the time, moves and stars possibly from different runs.
+#[derive(Clone, Copy)]
+pub struct LevelResult {
+    pub time: Duration,
+    pub moves: u32,
+    /// Rating from 1 to 3.
+    pub stars: u8,
+}
+
+/// Formats `time` as `m:ss`.
+pub fn format_time(time: Duration) -> String {
+    let secs = time.as_secs();
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+/// Progress that is kept between sessions.
+#[derive(Resource, Default)]
+pub struct Progress {
+    best: HashMap<u64, LevelResult>,
+}
+
+impl Progress {
+    const PATH: &str = "progress.txt";
+
+    fn load() -> Self {
+        let Ok(contents) = fs::read_to_string(Self::PATH) else {
+            return Self::default();
+        };
+        let mut best = HashMap::new();
+        for line in contents.lines() {
+            let mut values = line.split_whitespace();
+            let (Some(Ok(level)), Some(Ok(millis)), Some(Ok(moves)), Some(Ok(stars @ 1..=3))) = (
+                values.next().map(str::parse::<u64>),
+                values.next().map(str::parse::<u64>),
+                values.next().map(str::parse::<u32>),
+                values.next().map(str::parse::<u8>),
+            ) else {
+                warn!("Skipping invalid line in {}: {line}", Self::PATH);
+                continue;
+            };
+            best.insert(
+                level,
+                LevelResult {
+                    time: Duration::from_millis(millis),
+                    moves,
+                    stars,
+                },
+            );
+        }
+        Self { best }
+    }
+
+    fn save(&self) {
+        let mut levels = self.best.iter().collect::<Vec<_>>();
+        levels.sort_by_key(|(level, _)| **level);
+        let contents = levels
+            .into_iter()
+            .map(|(level, result)| {
+                format!(
+                    "{level} {} {} {}\n",
+                    result.time.as_millis(),
+                    result.moves,
+                    result.stars
+                )
+            })
+            .collect::<String>();
+        if let Err(e) = fs::write(Self::PATH, contents) {
+            warn!("Failed to save progress: {e}");
+        }
+    }
+
+    pub fn best(&self, level: u64) -> Option<LevelResult> {
+        self.best.get(&level).copied()
+    }
+
+    /// Sum of the best star ratings of all levels.
+    pub fn total_stars(&self) -> u32 {
+        self.best.values().map(|result| result.stars as u32).sum()
+    }
+
+    /// Keeps the better time, moves and stars of `result` and the previous best, then saves.
+    pub fn record(&mut self, level: u64, result: LevelResult) {
+        let best = self.best.entry(level).or_insert(result);
+        best.time = best.time.min(result.time);
+        best.moves = best.moves.min(result.moves);
+        best.stars = best.stars.max(result.stars);
+        self.save();
+    }
+}
@@ -0,0 +1,163 @@
+use std::fs;
+
+use bevy::prelude::*;
+
+use crate::{
+    audio::AudioSettings,
+    graphics::{BloomPreset, GraphicsSettings, ScreenMode},
+    input::Bindings,
+    locale::Language,
+    theme::Theme,
+};
+
+pub fn plugin(app: &mut App) {
+    app.insert_resource(Settings::load()).add_systems(
+        Update,
+        save.run_if(resource_changed::<Settings>.and(not(resource_added::<Settings>))),
+    );
+}
+
+/// Settings that are kept between sessions.
+#[derive(Resource)]
+pub struct Settings {
+    pub graphics: GraphicsSettings,
+    pub audio: AudioSettings,
+    pub theme: Theme,
+    pub language: Language,
+    /// Show vertex states with patterns and outlines in addition to colors.
+    pub shape_cues: bool,
+    /// Fade between levels instead of moving the camera.
+    pub reduced_motion: bool,
+    /// Animate solved vertices and levels.
+    pub effects: bool,
+    pub bindings: Bindings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            graphics: default(),
+            audio: default(),
+            theme: default(),
+            language: default(),
+            shape_cues: false,
+            reduced_motion: false,
+            effects: true,
+            bindings: default(),
+        }
+    }
+}
+
+impl Settings {
+    const PATH: &str = "settings.txt";
+
+    fn load() -> Self {
+        let mut settings = Self::default();
+        let Ok(contents) = fs::read_to_string(Self::PATH) else {
+            return settings;
+        };
+        for line in contents.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                warn!("Skipping invalid line in {}: {line}", Self::PATH);
+                continue;
+            };
+            if !settings.set(key.trim(), value.trim()) {
+                warn!("Skipping invalid setting in {}: {line}", Self::PATH);
+            }
+        }
+        settings
+    }
+
+    /// Sets the setting `key` from its saved `value`. Returns whether the setting was valid.
+    fn set(&mut self, key: &str, value: &str) -> bool {
+        fn parse<T: std::str::FromStr>(value: &str, field: &mut T) -> bool {
+            value.parse().map(|value| *field = value).is_ok()
+        }
+        /// Volumes are limited to the range of the volume buttons.
+        fn parse_volume(value: &str, field: &mut f32) -> bool {
+            value
+                .parse::<f32>()
+                .ok()
+                .filter(|value| value.is_finite())
+                .map(|value| *field = value.clamp(0.0, 1.0))
+                .is_some()
+        }
+        let graphics = &mut self.graphics;
+        match key {
+            "screen_mode" => ScreenMode::from_key(value)
+                .map(|mode| graphics.screen_mode = mode)
+                .is_some(),
+            "monitor" => parse(value, &mut graphics.monitor),
+            "resolution" => graphics.set_resolution_key(value),
+            "vsync" => parse(value, &mut graphics.vsync),
+            "msaa" => parse(value, &mut graphics.msaa),
+            "bloom" => BloomPreset::from_key(value)
+                .map(|preset| graphics.bloom = preset)
+                .is_some(),
+            "master_volume" => parse_volume(value, &mut self.audio.master),
+            "effects_volume" => parse_volume(value, &mut self.audio.effects),
+            "music_volume" => parse_volume(value, &mut self.audio.music),
+            "muted" => parse(value, &mut self.audio.muted),
+            "theme" => Theme::from_key(value)
+                .map(|theme| self.theme = theme)
+                .is_some(),
+            "language" => Language::from_key(value)
+                .map(|language| self.language = language)
+                .is_some(),
+            "shape_cues" => parse(value, &mut self.shape_cues),
+            "reduced_motion" => parse(value, &mut self.reduced_motion),
+            "effects" => parse(value, &mut self.effects),
+            _ => self.bindings.set(key, value),
+        }
+    }
+
+    fn to_file_contents(&self) -> String {
+        let Self {
+            graphics,
+            audio,
+            theme,
+            language,
+            shape_cues,
+            reduced_motion,
+            effects,
+            bindings,
+        } = self;
+        let mut contents = format!(
+            "screen_mode={}\n\
+            monitor={}\n\
+            resolution={}\n\
+            vsync={}\n\
+            msaa={}\n\
+            bloom={}\n\
+            master_volume={}\n\
+            effects_volume={}\n\
+            music_volume={}\n\
+            muted={}\n\
+            theme={}\n\
+            language={}\n\
+            shape_cues={shape_cues}\n\
+            reduced_motion={reduced_motion}\n\
+            effects={effects}\n",
+            graphics.screen_mode.key(),
+            graphics.monitor,
+            graphics.resolution_key(),
+            graphics.vsync,
+            graphics.msaa,
+            graphics.bloom.key(),
+            audio.master,
+            audio.effects,
+            audio.music,
+            audio.muted,
+            theme.key(),
+            language.key(),
+        );
+        contents += &bindings.to_file_contents();
+        contents
+    }
+}
+
+fn save(settings: Res<Settings>) {
+    if let Err(e) = fs::write(Settings::PATH, settings.to_file_contents()) {
+        warn!("Failed to save settings: {e}");
+    }
+}
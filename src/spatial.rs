@@ -0,0 +1,137 @@
+//! Grid that finds the points near a segment without looking at all of them.
+
+use bevy::{
+    math::bounding::{BoundingCircle, RayCast2d},
+    platform::collections::HashMap,
+    prelude::*,
+};
+
+/// Radius of a vertex, also available as `Vertex::RADIUS`.
+pub const VERTEX_RADIUS: f32 = 50.0;
+/// Longest edge between the sides of two vertices, also available as `Edge::MAX_LEN`.
+pub const MAX_EDGE_LEN: f32 = 400.0;
+/// Size of the cells of the game's vertex grid, which fits a few vertices.
+pub const GRID_CELL_SIZE: f32 = VERTEX_RADIUS * 4.0;
+
+/// Items at points, bucketed into square cells.
+pub struct SpatialGrid<T> {
+    cell_size: f32,
+    cells: HashMap<IVec2, Vec<(Vec2, T)>>,
+}
+
+impl<T: Copy> SpatialGrid<T> {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+        }
+    }
+
+    fn cell(&self, pos: Vec2) -> IVec2 {
+        (pos / self.cell_size).floor().as_ivec2()
+    }
+
+    pub fn insert(&mut self, pos: Vec2, item: T) {
+        let cell = self.cell(pos);
+        self.cells.entry(cell).or_default().push((pos, item));
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+    }
+
+    /// Items in the cells that overlap `rect`, which can include items outside of it.
+    pub fn in_rect(&self, rect: Rect) -> impl Iterator<Item = (Vec2, T)> + '_ {
+        let min = self.cell(rect.min);
+        let max = self.cell(rect.max);
+        (min.y..=max.y)
+            .flat_map(move |y| (min.x..=max.x).map(move |x| IVec2::new(x, y)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+    }
+
+    /// Items that may be within `radius` of the segment from `from` to `to`.
+    pub fn near_segment(
+        &self,
+        from: Vec2,
+        to: Vec2,
+        radius: f32,
+    ) -> impl Iterator<Item = (Vec2, T)> + '_ {
+        self.in_rect(Rect::from_corners(from, to).inflate(radius))
+    }
+}
+
+/// Returns the first point on the way from `from` to `to` where a circle of `radius` around
+/// one of the `obstacles` is in the way, or `to` if none is. The way starts at the edge of the
+/// circle around `from` and is at most `max_len` long.
+pub fn first_obstacle_pos(
+    from: Vec2,
+    to: Vec2,
+    radius: f32,
+    max_len: f32,
+    obstacles: impl Iterator<Item = Vec2>,
+) -> Vec2 {
+    let Ok(dir) = Dir2::new(to - from) else {
+        return to;
+    };
+    let ray = Ray2d::new(from + (radius + 0.1) * dir, dir);
+    let dist = (from.distance(to) - radius - 0.1).min(max_len);
+    let ray_cast = RayCast2d::from_ray(ray, dist);
+    let mut obstacle_dist = None;
+    for obstacle in obstacles {
+        let circle = BoundingCircle::new(obstacle, radius);
+        if let Some(result) = ray_cast.circle_intersection_at(&circle)
+            && (obstacle_dist.is_none() || obstacle_dist.unwrap() > result)
+        {
+            obstacle_dist = Some(result);
+        }
+    }
+    match obstacle_dist {
+        Some(dist) => ray.get_point(dist),
+        None => to,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    use super::*;
+
+    #[test]
+    fn grid_finds_same_obstacles_as_linear_scan() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let positions = (0..400)
+            .map(|_| {
+                Vec2::new(
+                    rng.gen_range(-2500.0..2500.0),
+                    rng.gen_range(-2500.0..2500.0),
+                )
+            })
+            .collect::<Vec<_>>();
+        let mut grid = SpatialGrid::new(GRID_CELL_SIZE);
+        for (i, pos) in positions.iter().enumerate() {
+            grid.insert(*pos, i);
+        }
+        for _ in 0..1000 {
+            let from = positions[rng.gen_range(0..positions.len())];
+            let to =
+                from + Vec2::from_angle(rng.gen_range(-PI..PI)) * rng.gen_range(0.0..MAX_EDGE_LEN);
+            let linear = first_obstacle_pos(
+                from,
+                to,
+                VERTEX_RADIUS,
+                MAX_EDGE_LEN,
+                positions.iter().copied(),
+            );
+            let obstacles = grid
+                .near_segment(from, to, VERTEX_RADIUS)
+                .map(|(pos, _)| pos);
+            let grid = first_obstacle_pos(from, to, VERTEX_RADIUS, MAX_EDGE_LEN, obstacles);
+            assert_eq!(linear, grid, "from {from} to {to}");
+        }
+    }
+}
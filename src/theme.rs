@@ -0,0 +1,199 @@
+use bevy::prelude::*;
+
+use crate::{edge::EdgeMaterial, settings::Settings, vertex::VertexMaterial};
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(PostUpdate, (apply_theme, apply_theme_colors));
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+    HighContrast,
+    Retro,
+}
+
+impl Theme {
+    pub const ALL: [Self; 4] = [Self::Dark, Self::Light, Self::HighContrast, Self::Retro];
+
+    /// Key of the theme's name in the string tables.
+    pub fn name_key(self) -> &'static str {
+        match self {
+            Self::Dark => "theme-dark",
+            Self::Light => "theme-light",
+            Self::HighContrast => "theme-high-contrast",
+            Self::Retro => "theme-retro",
+        }
+    }
+
+    /// Name used in the settings file.
+    pub fn key(self) -> &'static str {
+        match self {
+            Self::Dark => "dark",
+            Self::Light => "light",
+            Self::HighContrast => "high_contrast",
+            Self::Retro => "retro",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|theme| theme.key() == key)
+    }
+
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|theme| *theme == self).unwrap();
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    pub fn palette(self) -> Palette {
+        match self {
+            Self::Dark => Palette {
+                background: Color::BLACK,
+                foreground: Color::WHITE,
+                selected: Color::srgb(1.0, 0.5, 0.2),
+                over: Color::srgb(1.0, 0.1, 0.1),
+                panel: Color::WHITE,
+                panel_text: Color::BLACK,
+                star: Color::srgb(1.0, 0.8, 0.2),
+            },
+            Self::Light => Palette {
+                background: Color::srgb(0.95, 0.94, 0.9),
+                foreground: Color::srgb(0.1, 0.1, 0.15),
+                selected: Color::srgb(0.9, 0.4, 0.1),
+                over: Color::srgb(0.85, 0.1, 0.1),
+                panel: Color::srgb(0.15, 0.15, 0.2),
+                panel_text: Color::WHITE,
+                star: Color::srgb(0.8, 0.55, 0.0),
+            },
+            Self::HighContrast => Palette {
+                background: Color::BLACK,
+                foreground: Color::WHITE,
+                selected: Color::srgb(1.0, 1.0, 0.0),
+                over: Color::srgb(1.0, 0.0, 1.0),
+                panel: Color::BLACK,
+                panel_text: Color::WHITE,
+                star: Color::srgb(1.0, 1.0, 0.0),
+            },
+            Self::Retro => Palette {
+                background: Color::srgb(0.05, 0.09, 0.05),
+                foreground: Color::srgb(0.4, 1.0, 0.4),
+                selected: Color::srgb(1.0, 0.75, 0.2),
+                over: Color::srgb(1.0, 0.3, 0.2),
+                panel: Color::srgb(0.1, 0.2, 0.1),
+                panel_text: Color::srgb(0.4, 1.0, 0.4),
+                star: Color::srgb(1.0, 0.75, 0.2),
+            },
+        }
+    }
+}
+
+pub struct Palette {
+    pub background: Color,
+    /// Vertices, edges and text.
+    pub foreground: Color,
+    pub selected: Color,
+    /// Vertices with too many edges.
+    pub over: Color,
+    /// Background of the pause menus.
+    pub panel: Color,
+    pub panel_text: Color,
+    pub star: Color,
+}
+
+impl Palette {
+    pub fn hovered(&self) -> Color {
+        self.panel.mix(&self.panel_text, 0.2)
+    }
+
+    pub fn pressed(&self) -> Color {
+        self.panel.mix(&self.panel_text, 0.4)
+    }
+}
+
+/// Gives the entity's text, UI background or mesh a color of the current palette.
+#[derive(Component, Clone, Copy)]
+pub enum ThemeColor {
+    Background,
+    Foreground,
+    Panel,
+    PanelText,
+    Star,
+}
+
+impl ThemeColor {
+    fn get(self, palette: &Palette) -> Color {
+        match self {
+            Self::Background => palette.background,
+            Self::Foreground => palette.foreground,
+            Self::Panel => palette.panel,
+            Self::PanelText => palette.panel_text,
+            Self::Star => palette.star,
+        }
+    }
+}
+
+/// Recolors the background, all vertices and all edges when the theme or shape cue setting changes.
+fn apply_theme(
+    settings: Res<Settings>,
+    mut last_theme: Local<Option<(Theme, bool)>>,
+    mut clear_color: ResMut<ClearColor>,
+    vertex_q: Query<(&MeshMaterial2d<VertexMaterial>, &Children)>,
+    mut text_color_q: Query<&mut TextColor>,
+    mut vertex_materials: ResMut<Assets<VertexMaterial>>,
+    mut edge_materials: ResMut<Assets<EdgeMaterial>>,
+) {
+    let theme = (settings.theme, settings.shape_cues);
+    if *last_theme == Some(theme) {
+        return;
+    }
+    *last_theme = Some(theme);
+    let palette = settings.theme.palette();
+    clear_color.0 = palette.background;
+    for (_, material) in vertex_materials.iter_mut() {
+        material.set_palette(&palette);
+        material.set_shape_cues(settings.shape_cues);
+    }
+    for (_, material) in edge_materials.iter_mut() {
+        material.set_palette(&palette);
+    }
+    for (handle, children) in &vertex_q {
+        let (Some(material), Ok(mut text_color)) = (
+            vertex_materials.get(handle),
+            text_color_q.get_mut(children[0]),
+        ) else {
+            continue;
+        };
+        text_color.0 = material.text_color();
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn apply_theme_colors(
+    settings: Res<Settings>,
+    mut q: Query<(
+        Ref<ThemeColor>,
+        Option<&mut TextColor>,
+        Option<&mut BackgroundColor>,
+        Option<&MeshMaterial2d<ColorMaterial>>,
+    )>,
+    mut color_materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let palette = settings.theme.palette();
+    for (theme_color, text_color, bg, material) in &mut q {
+        if !settings.is_changed() && !theme_color.is_added() {
+            continue;
+        }
+        let color = theme_color.get(&palette);
+        if let Some(mut text_color) = text_color {
+            text_color.0 = color;
+        }
+        if let Some(mut bg) = bg {
+            bg.0 = color;
+        }
+        if let Some(material) = material.and_then(|handle| color_materials.get_mut(handle)) {
+            material.color = color;
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use bevy::prelude::*;
+
+use crate::settings::Settings;
+
+pub fn plugin(app: &mut App) {
+    app.add_systems(Startup, spawn_fade_overlay);
+}
+
+/// Maps the time of a transition from 0 to 1 onto its progress from 0 to 1.
+#[derive(Clone, Copy)]
+pub enum Easing {
+    Linear,
+    QuadraticIn,
+    QuadraticOut,
+    CubicIn,
+    CubicOut,
+}
+
+impl Easing {
+    pub fn ease(self, t: f32) -> f32 {
+        match self {
+            Self::Linear => t,
+            Self::QuadraticIn => t * t,
+            Self::QuadraticOut => 1.0 - (1.0 - t).powi(2),
+            Self::CubicIn => t * t * t,
+            Self::CubicOut => 1.0 - (1.0 - t).powi(3),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum TransitionKind {
+    /// Moves the camera from `from` to `to`, relative to the center of the view.
+    /// Each axis has its own easing.
+    Fly {
+        from: Vec2,
+        to: Vec2,
+        easing: [Easing; 2],
+    },
+    /// Fades the screen between two opacities of the background color.
+    Fade { from: f32, to: f32, easing: Easing },
+}
+
+/// How the screen changes when entering or leaving a level.
+#[derive(Clone, Copy)]
+pub struct Transition {
+    pub secs: f32,
+    pub kind: TransitionKind,
+}
+
+impl Transition {
+    const FLY_IN: Self = Self {
+        secs: 2.0,
+        kind: TransitionKind::Fly {
+            from: Vec2::new(-2000.0, 1000.0),
+            to: Vec2::ZERO,
+            easing: [Easing::QuadraticOut, Easing::CubicOut],
+        },
+    };
+    const FLY_OUT: Self = Self {
+        secs: 2.0,
+        kind: TransitionKind::Fly {
+            from: Vec2::ZERO,
+            to: Vec2::new(2000.0, -1000.0),
+            easing: [Easing::QuadraticIn, Easing::CubicIn],
+        },
+    };
+    const FADE_IN: Self = Self {
+        secs: 0.6,
+        kind: TransitionKind::Fade {
+            from: 1.0,
+            to: 0.0,
+            easing: Easing::Linear,
+        },
+    };
+    const FADE_OUT: Self = Self {
+        secs: 0.6,
+        kind: TransitionKind::Fade {
+            from: 0.0,
+            to: 1.0,
+            easing: Easing::Linear,
+        },
+    };
+
+    pub fn level_enter(settings: &Settings) -> Self {
+        if settings.reduced_motion {
+            Self::FADE_IN
+        } else {
+            Self::FLY_IN
+        }
+    }
+
+    pub fn level_exit(settings: &Settings) -> Self {
+        if settings.reduced_motion {
+            Self::FADE_OUT
+        } else {
+            Self::FLY_OUT
+        }
+    }
+
+    /// Returns the camera offset from the center of the view and the opacity of the fade
+    /// overlay at time `t` from 0 to 1.
+    pub fn sample(&self, t: f32) -> (Vec2, f32) {
+        match self.kind {
+            TransitionKind::Fly { from, to, easing } => {
+                let progress = Vec2::new(easing[0].ease(t), easing[1].ease(t));
+                (from + (to - from) * progress, 0.0)
+            }
+            TransitionKind::Fade { from, to, easing } => {
+                (Vec2::ZERO, from + (to - from) * easing.ease(t))
+            }
+        }
+    }
+}
+
+/// Covers the screen in the background color during fade transitions.
+#[derive(Component)]
+pub struct FadeOverlay;
+
+fn spawn_fade_overlay(mut commands: Commands) {
+    commands.spawn((
+        FadeOverlay,
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            ..default()
+        },
+        BackgroundColor(Color::NONE),
+        GlobalZIndex(1),
+        Pickable::IGNORE,
+    ));
+}
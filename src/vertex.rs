@@ -1,5 +1,5 @@
 use bevy::{
-    platform::collections::HashSet,
+    platform::collections::HashMap,
     prelude::*,
     render::render_resource::{AsBindGroup, ShaderRef},
     sprite::{Material2d, Material2dPlugin},
@@ -7,13 +7,19 @@ use bevy::{
 
 use crate::{
     GameState,
-    audio::{PlaceAudioHandle, SelectAudioHandle},
-    edge::{Edge, get_obstacle_pos, handle_edge_click},
-    level::CheckIfSolvedSystem,
+    audio::SoundEffects,
+    edge::{Edge, EdgeMaterial, get_obstacle_pos, handle_edge_click, remove_edge},
+    level::{CheckIfSolvedSystem, LevelRules, LevelStats},
+    meshes::SharedMeshes,
+    settings::Settings,
+    spatial::{self, SpatialGrid},
+    theme::Palette,
 };
 
 pub fn plugin(app: &mut App) {
-    app.add_plugins(Material2dPlugin::<VertexMaterial>::default());
+    app.add_plugins(Material2dPlugin::<VertexMaterial>::default())
+        .insert_resource(VertexGrid(SpatialGrid::new(Vertex::GRID_CELL_SIZE)))
+        .add_systems(PostUpdate, index_vertices);
 }
 
 #[derive(Component)]
@@ -23,37 +29,64 @@ pub struct Selected {
 
 #[derive(Component)]
 pub struct Vertex {
-    pub edges: HashSet<Entity>,
+    /// Edge to each connected vertex, keyed by the connected vertex.
+    pub edges: HashMap<Entity, Entity>,
     pub required_edges: usize,
     start_pos: Vec2,
 }
 
 impl Vertex {
-    pub const RADIUS: f32 = 50.0;
+    pub const RADIUS: f32 = spatial::VERTEX_RADIUS;
+    /// How far a vertex can be dragged away from its starting position.
+    const MAX_DRAG: f32 = 100.0;
+    /// Size of the cells of [`VertexGrid`].
+    pub const GRID_CELL_SIZE: f32 = spatial::GRID_CELL_SIZE;
 
     pub fn new(required_edges: usize, start_pos: Vec2) -> Self {
         Self {
-            edges: HashSet::new(),
+            edges: HashMap::new(),
             required_edges,
             start_pos,
         }
     }
 
+    /// Scale of a vertex, which sizes the shared unit circle.
+    pub const SCALE: Vec3 = Vec3::new(Self::RADIUS, Self::RADIUS, 1.0);
+
+    /// Transform of a child at `offset` from the center of a vertex that undoes the scale of
+    /// the vertex, so text on it keeps its size.
+    pub fn child_transform(offset: Vec2) -> Transform {
+        Transform::from_translation((offset / Self::RADIUS).extend(0.0))
+            .with_scale(Self::SCALE.recip())
+    }
+
     pub fn spawn(
         self,
         z: f32,
+        settings: &Settings,
+        rules: &LevelRules,
         mut commands: Commands,
-        mut meshes: Mut<Assets<Mesh>>,
+        meshes: &SharedMeshes,
         mut materials: Mut<Assets<VertexMaterial>>,
-    ) {
+    ) -> Entity {
         let required = self.required_edges;
         let pos = self.start_pos.extend(z);
+        let material = VertexMaterial {
+            // No progress ring is drawn without a required count.
+            required: if rules.progress_rings {
+                required as u32
+            } else {
+                0
+            },
+            ..VertexMaterial::new(settings)
+        };
+        let text_color = material.text_color();
         commands
             .spawn((
                 self,
-                Mesh2d(meshes.add(Circle::new(Self::RADIUS))),
-                MeshMaterial2d(materials.add(VertexMaterial { bits: 0 })),
-                Transform::from_translation(pos),
+                Mesh2d(meshes.circle.clone()),
+                MeshMaterial2d(materials.add(material)),
+                Transform::from_translation(pos).with_scale(Self::SCALE),
             ))
             .with_child((
                 Text2d(format!("{required}")),
@@ -61,9 +94,49 @@ impl Vertex {
                     font_size: 70.0,
                     ..default()
                 },
+                TextColor(text_color),
+                Self::child_transform(Vec2::ZERO),
             ))
             .observe(handle_vertex_click)
-            .observe(handle_vertex_drag);
+            .observe(handle_vertex_drag)
+            .id()
+    }
+}
+
+/// Vertices by their starting position, so obstacle checks only look at nearby vertices.
+#[derive(Resource)]
+pub struct VertexGrid(SpatialGrid<Entity>);
+
+impl VertexGrid {
+    /// Vertices that may overlap the segment from `from` to `to`, wherever they were
+    /// dragged to.
+    pub fn near_segment(&self, from: Vec2, to: Vec2) -> impl Iterator<Item = Entity> + '_ {
+        self.0
+            .near_segment(from, to, Vertex::RADIUS + Vertex::MAX_DRAG)
+            .map(|(_, entity)| entity)
+    }
+
+    /// Vertices whose centers may be within `dist` of `pos`.
+    pub fn near(&self, pos: Vec2, dist: f32) -> impl Iterator<Item = Entity> + '_ {
+        self.0
+            .near_segment(pos, pos, dist + Vertex::MAX_DRAG)
+            .map(|(_, entity)| entity)
+    }
+}
+
+/// Rebuilds the grid when vertices are spawned or despawned.
+fn index_vertices(
+    new_vertex_q: Query<(), Added<Vertex>>,
+    mut removed_vertices: RemovedComponents<Vertex>,
+    vertex_q: Query<(Entity, &Vertex)>,
+    mut grid: ResMut<VertexGrid>,
+) {
+    if new_vertex_q.is_empty() && removed_vertices.read().count() == 0 {
+        return;
+    }
+    grid.0.clear();
+    for (entity, vertex) in &vertex_q {
+        grid.0.insert(vertex.start_pos, entity);
     }
 }
 
@@ -71,8 +144,24 @@ impl Vertex {
 pub struct VertexMaterial {
     /// 1 << 0: selected
     /// 1 << 1: solved
+    /// 1 << 2: over-saturated (more edges than required)
+    /// 1 << 3: draw shape cues for the states above, not only colors
     #[uniform(0)]
     pub bits: u32,
+    /// Number of edges connected to the vertex.
+    #[uniform(0)]
+    pub placed: u32,
+    /// Number of edges the vertex needs. No progress ring is drawn if this is 0.
+    #[uniform(0)]
+    pub required: u32,
+    #[uniform(0)]
+    background: LinearRgba,
+    #[uniform(0)]
+    foreground: LinearRgba,
+    #[uniform(0)]
+    selected: LinearRgba,
+    #[uniform(0)]
+    over: LinearRgba,
 }
 
 impl Material2d for VertexMaterial {
@@ -82,6 +171,46 @@ impl Material2d for VertexMaterial {
 }
 
 impl VertexMaterial {
+    pub fn new(settings: &Settings) -> Self {
+        let mut material = Self {
+            bits: 0,
+            placed: 0,
+            required: 0,
+            background: default(),
+            foreground: default(),
+            selected: default(),
+            over: default(),
+        };
+        material.set_palette(&settings.theme.palette());
+        material.set_shape_cues(settings.shape_cues);
+        material
+    }
+
+    pub fn set_palette(&mut self, palette: &Palette) {
+        self.background = palette.background.into();
+        self.foreground = palette.foreground.into();
+        self.selected = palette.selected.into();
+        self.over = palette.over.into();
+    }
+
+    /// Color of the number on the vertex, which is drawn on the background color unless the
+    /// vertex is solved.
+    pub fn text_color(&self) -> Color {
+        if self.bits & 2 != 0 {
+            self.background.into()
+        } else {
+            self.foreground.into()
+        }
+    }
+
+    pub fn set_shape_cues(&mut self, v: bool) {
+        if v {
+            self.bits |= 8;
+        } else {
+            self.bits &= !8;
+        }
+    }
+
     fn set_selected(&mut self, v: bool) {
         if v {
             self.bits |= 1;
@@ -93,10 +222,32 @@ impl VertexMaterial {
     pub fn set_solved(&mut self, v: bool, text_color: &mut TextColor) {
         if v {
             self.bits |= 2;
-            text_color.0 = Color::BLACK;
         } else {
             self.bits &= !2;
-            text_color.0 = Color::WHITE;
+        }
+        text_color.0 = self.text_color();
+    }
+
+    /// Updates the progress ring and the solved and over-saturated states.
+    /// Returns whether the vertex just became solved.
+    pub fn set_edge_count(
+        &mut self,
+        placed: usize,
+        required: usize,
+        text_color: &mut TextColor,
+    ) -> bool {
+        let was_solved = self.bits & 2 != 0;
+        self.placed = placed as u32;
+        self.set_solved(placed == required, text_color);
+        self.set_over(placed > required);
+        !was_solved && placed == required
+    }
+
+    fn set_over(&mut self, v: bool) {
+        if v {
+            self.bits |= 4;
+        } else {
+            self.bits &= !4;
         }
     }
 }
@@ -115,13 +266,16 @@ fn handle_vertex_click(
     mesh_material_q: Query<&MeshMaterial2d<VertexMaterial>>,
     mut text_color_q: Query<&mut TextColor>,
     mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut color_materials: ResMut<Assets<ColorMaterial>>,
+    meshes: Res<SharedMeshes>,
+    mut edge_materials: ResMut<Assets<EdgeMaterial>>,
     mut vertex_materials: ResMut<Assets<VertexMaterial>>,
-    select_audio: Res<SelectAudioHandle>,
-    place_audio: Res<PlaceAudioHandle>,
+    sound_effects: SoundEffects,
     check_if_solved_system: Res<CheckIfSolvedSystem>,
+    rules: Res<LevelRules>,
+    mut stats: ResMut<LevelStats>,
     state: Res<State<GameState>>,
+    settings: Res<Settings>,
+    grid: Res<VertexGrid>,
 ) {
     if *state.get() != GameState::Playing {
         return;
@@ -150,25 +304,20 @@ fn handle_vertex_click(
         };
         transform.translation.z += 1.0;
 
-        let dist = transform.translation.xy().distance(pointer_pos.xy());
         let edge = commands
             .spawn((
                 Edge(entity, Entity::PLACEHOLDER),
-                Mesh2d(meshes.add(Rectangle::new(dist, Edge::WIDTH))),
-                MeshMaterial2d(color_materials.add(Color::WHITE)),
-                Transform {
-                    translation: ((transform.translation.xy() + pointer_pos.xy()) / 2.0)
-                        .extend(-1.0),
-                    rotation: {
-                        let diff = transform.translation - pointer_pos;
-                        Quat::from_rotation_z(diff.y.atan2(diff.x))
-                    },
-                    ..default()
-                },
-                AudioPlayer(select_audio.0.clone()),
-                PlaybackSettings::REMOVE,
+                Edge::bundle(
+                    transform.translation.xy(),
+                    pointer_pos.xy(),
+                    -1.0,
+                    EdgeMaterial::preview(&settings),
+                    &meshes,
+                    &mut edge_materials,
+                ),
             ))
             .id();
+        commands.spawn(sound_effects.select(transform.translation.xy()));
 
         commands.entity(trigger.target()).insert(Selected { edge });
         return;
@@ -186,18 +335,13 @@ fn handle_vertex_click(
 
     commands.entity(selected.edge).despawn();
 
-    if get_obstacle_pos(
-        selected_transform.translation.xy(),
-        pointer_pos.xy(),
-        vertex_q.iter().filter_map(|(e, _, transform, _)| {
-            if e == trigger.target() {
-                None
-            } else {
-                Some(transform)
-            }
-        }),
-    ) != pointer_pos.xy()
-    {
+    let selected_pos = selected_transform.translation.xy();
+    let obstacles = grid
+        .near_segment(selected_pos, pointer_pos.xy())
+        .filter(|entity| *entity != trigger.target())
+        .filter_map(|entity| vertex_q.get(entity).ok())
+        .map(|(_, _, transform, _)| transform.translation.xy());
+    if get_obstacle_pos(selected_pos, pointer_pos.xy(), obstacles) != pointer_pos.xy() {
         return;
     }
 
@@ -205,7 +349,7 @@ fn handle_vertex_click(
         // Unselect vertex.
         return;
     };
-    if selected_vertex.edges.contains(&entity) {
+    if selected_vertex.edges.contains_key(&entity) {
         // Edge already exists.
         return;
     }
@@ -218,38 +362,43 @@ fn handle_vertex_click(
     if dist > Edge::MAX_LEN + Vertex::RADIUS * 2.0 {
         return;
     }
+    if !rules.allow_over_saturation
+        && (selected_vertex.edges.len() >= selected_vertex.required_edges
+            || vertex.edges.len() >= vertex.required_edges)
+    {
+        return;
+    }
     // Despawning `selected.edge` and spawning new edge to avoid bug with removing edges.
     // See bug in commit f650d38.
-    commands
+    let edge = commands
         .spawn((
             Edge(selected_entity, entity),
-            Mesh2d(meshes.add(Rectangle::new(dist, Edge::WIDTH))),
-            MeshMaterial2d(color_materials.add(Color::WHITE)),
-            Transform {
-                translation: ((selected_transform.translation.xy() + transform.translation.xy())
-                    / 2.0)
-                    .extend(-1.0),
-                rotation: {
-                    let diff = transform.translation - selected_transform.translation;
-                    Quat::from_rotation_z(diff.y.atan2(diff.x))
-                },
-                ..default()
-            },
-            AudioPlayer(place_audio.0.clone()),
-            PlaybackSettings::REMOVE,
+            Edge::bundle(
+                selected_transform.translation.xy(),
+                transform.translation.xy(),
+                -1.0,
+                EdgeMaterial::new(&settings),
+                &meshes,
+                &mut edge_materials,
+            ),
         ))
-        .observe(handle_edge_click);
+        .observe(handle_edge_click)
+        .observe(remove_edge)
+        .id();
+    commands.spawn(sound_effects.place(transform.translation.xy(), vertex.required_edges));
+    stats.moves += 1;
 
-    selected_vertex.edges.insert(entity);
+    selected_vertex.edges.insert(entity, edge);
     let Ok(mut text_color) = text_color_q.get_mut(selected_children[0]) else {
         return;
     };
-    selected_material.set_solved(
-        selected_vertex.edges.len() == selected_vertex.required_edges,
+    let mut became_solved = selected_material.set_edge_count(
+        selected_vertex.edges.len(),
+        selected_vertex.required_edges,
         &mut text_color,
     );
 
-    vertex.edges.insert(selected_entity);
+    vertex.edges.insert(selected_entity, edge);
     let Ok(handle) = mesh_material_q.get(entity) else {
         return;
     };
@@ -259,7 +408,11 @@ fn handle_vertex_click(
     let Ok(mut text_color) = text_color_q.get_mut(children[0]) else {
         return;
     };
-    material.set_solved(vertex.edges.len() == vertex.required_edges, &mut text_color);
+    became_solved |=
+        material.set_edge_count(vertex.edges.len(), vertex.required_edges, &mut text_color);
+    if became_solved {
+        commands.spawn(sound_effects.vertex_solved(transform.translation.xy()));
+    }
 
     commands.run_system(check_if_solved_system.0);
 }
@@ -267,8 +420,7 @@ fn handle_vertex_click(
 fn handle_vertex_drag(
     trigger: Trigger<Pointer<Drag>>,
     mut vertex_q: Query<(&Vertex, &mut Transform)>,
-    mut edge_q: Query<(&Edge, &mut Transform, &Mesh2d), Without<Vertex>>,
-    mut meshes: ResMut<Assets<Mesh>>,
+    mut edge_q: Query<&mut Transform, (With<Edge>, Without<Vertex>)>,
     state: Res<State<GameState>>,
 ) {
     if *state.get() != GameState::Playing {
@@ -284,34 +436,19 @@ fn handle_vertex_drag(
         transform.translation.x + delta.x,
         transform.translation.y - delta.y,
     );
-    if new_pos.distance_squared(vertex.start_pos) > 10000.0 {
+    if new_pos.distance(vertex.start_pos) > Vertex::MAX_DRAG {
         return;
     }
 
-    if !vertex.edges.is_empty() {
-        for (edge, mut edge_transform, mesh2d) in &mut edge_q {
-            let other = if edge.0 == entity {
-                edge.1
-            } else if edge.1 == entity {
-                edge.0
-            } else {
-                continue;
-            };
-
-            let Some(mesh) = meshes.get_mut(mesh2d) else {
-                return;
-            };
-            let Ok((_, other_transform)) = vertex_q.get(other) else {
-                return;
-            };
-            let other_pos = other_transform.translation.xy();
-            let dist = new_pos.distance(other_pos);
-            *mesh = Rectangle::new(dist, Edge::WIDTH).into();
-
-            edge_transform.translation = ((new_pos + other_pos) / 2.0).extend(-1.0);
-            let diff = new_pos - other_pos;
-            edge_transform.rotation = Quat::from_rotation_z(diff.y.atan2(diff.x));
-        }
+    for (other, edge) in &vertex.edges {
+        let Ok(mut edge_transform) = edge_q.get_mut(*edge) else {
+            continue;
+        };
+        let Ok((_, other_transform)) = vertex_q.get(*other) else {
+            return;
+        };
+        let other_pos = other_transform.translation.xy();
+        Edge::set_ends(other_pos, new_pos, &mut edge_transform);
     }
 
     let (_, mut transform) = vertex_q.get_mut(entity).unwrap();